@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use ml_battle_snake::{
+    ml_snake::{agent::Agent, baselines::Baseline},
+    simulation::{
+        bitboard::FastBoard,
+        game::{GameWrapper, SnakeSpawn},
+        rules::{self, Direction, GameMode, Ruleset},
+    },
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+/// A 4 snake Standard game some turns in, with the moves the flood-fill baseline makes next
+fn midgame() -> (GameWrapper, Vec<Direction>) {
+    let mut game = GameWrapper::new(11, 11, 4, GameMode::Standard, SnakeSpawn::Official, 0);
+
+    let mut agents: HashMap<String, Box<dyn Agent>> = game
+        .board
+        .snakes
+        .iter()
+        .map(|snake| {
+            (
+                snake.id.clone(),
+                Baseline::FloodFill.agent(0) as Box<dyn Agent>,
+            )
+        })
+        .collect();
+
+    for _ in 0..20 {
+        game.turn_step(&mut agents);
+    }
+
+    let moves = game
+        .board
+        .snakes
+        .iter()
+        .map(|snake| {
+            agents
+                .get_mut(&snake.id)
+                .and_then(|agent| agent.get_move(&game.game, &game.turn, &game.board, snake))
+                .unwrap_or(Direction::Up)
+        })
+        .collect();
+
+    (game, moves)
+}
+
+fn step(c: &mut Criterion) {
+    let (game, moves) = midgame();
+    let ruleset = Ruleset::from_game(&game.game);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    c.bench_function("rules::step", |b| {
+        b.iter(|| rules::step(&game.board, game.turn, &ruleset, &moves, &mut rng))
+    });
+
+    let fast_board = FastBoard::from_board(&game.board);
+    let mut eliminated = Vec::new();
+
+    c.bench_function("FastBoard::step", |b| {
+        b.iter_batched_ref(
+            || fast_board.clone(),
+            |fast_board| {
+                eliminated.clear();
+                fast_board.step(game.turn, &ruleset, &moves, &mut rng, &mut eliminated);
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    // What a game pays each turn: loading its board into the `FastBoard` it keeps, stepping it and
+    // bringing the API board up to date
+
+    let game_moves: Vec<Option<Direction>> = moves.iter().copied().map(Some).collect();
+
+    c.bench_function("GameWrapper::apply_moves", |b| {
+        b.iter_batched_ref(
+            || game.clone(),
+            |game| game.apply_moves(&game_moves),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, step);
+criterion_main!(benches);
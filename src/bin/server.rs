@@ -2,22 +2,125 @@
 extern crate rocket;
 
 use log::info;
-use ml_battle_snake::ml_snake::logic;
+use ml_battle_snake::ml_snake::{
+    agent::{Agent, NeuralNetworkAgent},
+    logic,
+    multiplayer::{Backup, MultiplayerAgent},
+    search::HeuristicEvaluator,
+};
 use rocket::fairing::AdHoc;
 use rocket::http::Status;
-use rocket::serde::{json::Json, Deserialize};
+use rocket::serde::json::Json;
+use rocket::State;
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use ml_battle_snake::{GameState};
+use ml_battle_snake::{
+    constants::AGENT_IDLE_TIMEOUT_SECS,
+    neural_network::{NeuralNetwork, NeuralNetworkManager},
+    simulation::rules::{GameMode, Ruleset},
+    utils::build_neural_network,
+    GameState,
+};
 
 #[get("/")]
 fn handle_index() -> Json<Value> {
     Json(logic::info())
 }
 
+/// One snake's agent, locked on its own so that games don't wait on each other's moves
+type SharedAgent = Arc<Mutex<Box<dyn Agent + Send>>>;
+
+struct AgentEntry {
+    agent: SharedAgent,
+    /// When the game last asked the agent for anything
+    last_seen: Instant,
+}
+
+/// The agents playing the games in progress, keyed by game and snake. The map is only locked
+/// to find or change an entry, never while an agent plays. Agents of games that go quiet for
+/// `AGENT_IDLE_TIMEOUT_SECS` are dropped, so games that never end don't pile up
+#[derive(Default)]
+struct Agents(Mutex<HashMap<String, AgentEntry>>);
+
+impl Agents {
+    fn insert(&self, key: String, agent: Box<dyn Agent + Send>) {
+        let mut entries = self.0.lock().unwrap();
+        let now = Instant::now();
+
+        evict_idle(&mut entries, now);
+        entries.insert(
+            key,
+            AgentEntry {
+                agent: Arc::new(Mutex::new(agent)),
+                last_seen: now,
+            },
+        );
+    }
+
+    fn get_or_insert_with(
+        &self,
+        key: String,
+        new_agent: impl FnOnce() -> Box<dyn Agent + Send>,
+    ) -> SharedAgent {
+        let mut entries = self.0.lock().unwrap();
+        let now = Instant::now();
+
+        evict_idle(&mut entries, now);
+
+        let entry = entries.entry(key).or_insert_with(|| AgentEntry {
+            agent: Arc::new(Mutex::new(new_agent())),
+            last_seen: now,
+        });
+        entry.last_seen = now;
+
+        entry.agent.clone()
+    }
+
+    fn remove(&self, key: &str) -> Option<SharedAgent> {
+        self.0.lock().unwrap().remove(key).map(|entry| entry.agent)
+    }
+}
+
+fn evict_idle(entries: &mut HashMap<String, AgentEntry>, now: Instant) {
+    let idle_timeout = Duration::from_secs(AGENT_IDLE_TIMEOUT_SECS);
+
+    entries.retain(|_, entry| now.duration_since(entry.last_seen) < idle_timeout);
+}
+
+fn agent_key(game_state: &GameState) -> String {
+    format!("{}:{}", game_state.game.id, game_state.you.id)
+}
+
+/// The agent that plays a new game. Standard games of 3 or 4 snakes are searched, backing up scores
+/// by the rule named in the `SEARCH_BACKUP` environment variable, paranoid unless it says max_n.
+/// Other games are played by a network
+fn new_agent(game_state: &GameState) -> Box<dyn Agent + Send> {
+    let mode = Ruleset::from_game(&game_state.game).mode;
+
+    if mode == GameMode::Standard && (3..=4).contains(&game_state.board.snakes.len()) {
+        let backup = env::var("SEARCH_BACKUP")
+            .ok()
+            .and_then(|name| Backup::from_name(&name))
+            .unwrap_or_default();
+
+        return Box::new(MultiplayerAgent::new(HeuristicEvaluator::default(), backup));
+    }
+
+    let mut neural_network_manager = NeuralNetworkManager::new();
+    let mut neural_network = NeuralNetwork::new(&mut neural_network_manager);
+
+    build_neural_network(&mut neural_network, &game_state.board.geometry());
+    neural_network.mutate(&mut rand::thread_rng());
+
+    Box::new(NeuralNetworkAgent::new(neural_network))
+}
+
 #[post("/start", format = "json", data = "<start_req>")]
-fn handle_start(start_req: Json<GameState>) -> Status {
+fn handle_start(start_req: Json<GameState>, agents: &State<Agents>) -> Status {
     logic::start(
         &start_req.game,
         &start_req.turn,
@@ -25,17 +128,39 @@ fn handle_start(start_req: Json<GameState>) -> Status {
         &start_req.you,
     );
 
+    let mut agent = new_agent(&start_req);
+    agent.start(
+        &start_req.game,
+        &start_req.turn,
+        &start_req.board,
+        &start_req.you,
+    );
+
+    agents.insert(agent_key(&start_req), agent);
+
     Status::Ok
 }
 
 #[post("/move", format = "json", data = "<move_req>")]
-fn handle_move(move_req: Json<GameState>) -> Json<Value> {
-    let chosen_move = logic::get_move(
-        &move_req.game,
-        &move_req.turn,
-        &move_req.board,
-        &move_req.you,
-    );
+async fn handle_move(move_req: Json<GameState>, agents: &State<Agents>) -> Json<Value> {
+    // Games we missed the start of, such as after a restart, get an agent on their first move
+
+    let agent = agents.get_or_insert_with(agent_key(&move_req), || new_agent(&move_req));
+    let move_req = move_req.into_inner();
+
+    // Searches take most of the timeout, so they run off the threads serving requests
+
+    let chosen_move = rocket::tokio::task::spawn_blocking(move || {
+        agent.lock().unwrap().get_move(
+            &move_req.game,
+            &move_req.turn,
+            &move_req.board,
+            &move_req.you,
+        )
+    })
+    .await
+    .ok()
+    .flatten();
 
     println!("Chosen move: {:?}", chosen_move);
 
@@ -43,14 +168,21 @@ fn handle_move(move_req: Json<GameState>) -> Json<Value> {
         return Json(json!({}));
     };
 
-    let json_move = json!({ "move": chosen_move });
+    let json_move = json!({ "move": chosen_move.name() });
     Json(json_move)
 }
 
 #[post("/end", format = "json", data = "<end_req>")]
-fn handle_end(end_req: Json<GameState>) -> Status {
+fn handle_end(end_req: Json<GameState>, agents: &State<Agents>) -> Status {
     logic::end(&end_req.game, &end_req.turn, &end_req.board, &end_req.you);
 
+    if let Some(agent) = agents.remove(&agent_key(&end_req)) {
+        agent
+            .lock()
+            .unwrap()
+            .end(&end_req.game, &end_req.turn, &end_req.board, &end_req.you);
+    }
+
     Status::Ok
 }
 
@@ -74,6 +206,7 @@ fn rocket() -> _ {
     info!("Starting Battlesnake Server...");
 
     rocket::build()
+        .manage(Agents::default())
         .attach(AdHoc::on_response("Server ID Middleware", |_, res| {
             Box::pin(async move {
                 res.set_raw_header("Server", "battlesnake/github/starter-snake-rust");
@@ -83,4 +216,4 @@ fn rocket() -> _ {
             "/",
             routes![handle_index, handle_start, handle_move, handle_end],
         )
-}
\ No newline at end of file
+}
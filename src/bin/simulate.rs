@@ -1,8 +1,16 @@
-use ml_battle_snake::simulation::simulation::{self, Simulation};
+use ml_battle_snake::simulation::simulation::Simulation;
 
 #[tokio::main]
 async fn main() {
-    let mut simulation = Simulation::new();
+    // Pass a seed to replay a previous run, otherwise a new one is picked and printed
+    let seed = std::env::args()
+        .nth(1)
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or_else(rand::random);
+
+    println!("Seed {}", seed);
+
+    let mut simulation = Simulation::new(seed);
 
     let games_count = 20;
     let width = 11;
@@ -10,5 +18,7 @@ async fn main() {
     let snakes_count = 2;
     let rounds_count = 10000;
 
-    simulation.run_tournament(games_count, width, height, snakes_count, rounds_count).await;
-}
\ No newline at end of file
+    simulation
+        .run_tournament(games_count, width, height, snakes_count, rounds_count)
+        .await;
+}
@@ -0,0 +1,19 @@
+// Time kept back from `Game.timeout` by searching agents, to cover the round trip to the engine
+pub const SEARCH_LATENCY_MARGIN_MS: u64 = 100;
+
+// The deepest a search goes, in turns, when it has time to spare
+pub const DEFAULT_MAX_SEARCH_DEPTH: u32 = 8;
+
+// The score of a won game to a search, far beyond anything an evaluator gives. Wins a turn later score
+// a point less, so searches take the quickest win and put off losses
+pub const WIN_SCORE: f32 = 1_000_000.;
+
+// How strongly tree search favours moves it hasn't tried much over those that have done well
+pub const DEFAULT_EXPLORATION: f32 = 1.4;
+
+// The lead in heuristic score over the best opponent that tree search values at about 73% to win
+pub const HEURISTIC_VALUE_SCALE: f32 = 5.;
+
+// How long the server keeps an agent for a game it hears nothing more from, such as one whose
+// `/end` never arrived
+pub const AGENT_IDLE_TIMEOUT_SECS: u64 = 300;
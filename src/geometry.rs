@@ -0,0 +1,96 @@
+use crate::{simulation::rules::Direction, Coord};
+
+/// The shape of a board: its size, whether its edges wrap, and how its cells are numbered.
+/// Cells are packed row by row, so a cell's index is `y * width + x`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Geometry {
+    pub width: i32,
+    pub height: i32,
+    pub is_wrapped: bool,
+}
+
+impl Geometry {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            is_wrapped: false,
+        }
+    }
+
+    pub fn with_wrapping(self, is_wrapped: bool) -> Self {
+        Self { is_wrapped, ..self }
+    }
+
+    /// The number of cells on the board
+    pub fn area(&self) -> usize {
+        (self.width * self.height) as usize
+    }
+
+    pub fn pack(&self, coord: Coord) -> usize {
+        (coord.y * self.width + coord.x) as usize
+    }
+
+    pub fn unpack(&self, index: usize) -> Coord {
+        Coord {
+            x: index as i32 % self.width,
+            y: index as i32 / self.width,
+        }
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.x >= 0 && coord.x < self.width && coord.y >= 0 && coord.y < self.height
+    }
+
+    /// Brings a coordinate that has left the board back in from the opposite side
+    pub fn wrap(&self, coord: Coord) -> Coord {
+        Coord {
+            x: coord.x.rem_euclid(self.width),
+            y: coord.y.rem_euclid(self.height),
+        }
+    }
+
+    /// The on-board cell for `coord`: wrapped back onto the board when edges wrap,
+    /// otherwise `None` when it is out of bounds
+    pub fn on_board(&self, coord: Coord) -> Option<Coord> {
+        if self.is_wrapped {
+            return Some(self.wrap(coord));
+        }
+
+        if !self.contains(coord) {
+            return None;
+        }
+
+        Some(coord)
+    }
+
+    /// The number of moves between two cells on an empty board, going across the edges when they wrap
+    pub fn distance(&self, a: Coord, b: Coord) -> i32 {
+        let x = (a.x - b.x).abs();
+        let y = (a.y - b.y).abs();
+
+        if self.is_wrapped {
+            return x.min(self.width - x) + y.min(self.height - y);
+        }
+
+        x + y
+    }
+
+    /// The cell reached by moving from `coord` in `direction`, if it is on the board
+    pub fn neighbor(&self, coord: Coord, direction: Direction) -> Option<Coord> {
+        self.on_board(direction.apply(coord))
+    }
+
+    /// Every on-board cell next to `coord`, with the move that reaches it
+    pub fn neighbors(&self, coord: Coord) -> impl Iterator<Item = (Direction, Coord)> + '_ {
+        Direction::ALL.into_iter().filter_map(move |direction| {
+            self.neighbor(coord, direction)
+                .map(|neighbor| (direction, neighbor))
+        })
+    }
+
+    /// Every cell on the board, in packed order
+    pub fn coords(&self) -> impl Iterator<Item = Coord> + '_ {
+        (0..self.area()).map(|index| self.unpack(index))
+    }
+}
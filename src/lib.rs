@@ -1,15 +1,14 @@
 #[macro_use]
 extern crate rocket;
 
-use log::info;
-use rocket::fairing::AdHoc;
-use rocket::http::Status;
-use rocket::serde::{json::Json, Deserialize};
+use rocket::serde::Deserialize;
 use serde::Serialize;
-use serde_json::{json, Value};
+use serde_json::Value;
 use std::collections::HashMap;
-use std::env;
 
+use geometry::Geometry;
+
+pub mod geometry;
 pub mod ml_snake;
 pub mod neural_network;
 pub mod utils;
@@ -21,41 +20,51 @@ pub mod simulation;
 // API and Response Objects
 // See https://docs.battlesnake.com/api
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Game {
-    id: String,
-    ruleset: HashMap<String, Value>,
-    timeout: u32,
+    pub id: String,
+    pub ruleset: HashMap<String, Value>,
+    pub timeout: u32,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Board {
-    height: u32,
-    width: i32,
-    food: Vec<Coord>,
-    snakes: Vec<Battlesnake>,
-    hazards: Vec<Coord>,
+    pub height: i32,
+    pub width: i32,
+    pub food: Vec<Coord>,
+    pub snakes: Vec<Battlesnake>,
+    pub hazards: Vec<Coord>,
+}
+
+impl Board {
+    /// The board's size and cell numbering. Its edges don't wrap, since that depends on the ruleset
+    pub fn geometry(&self) -> Geometry {
+        Geometry::new(self.width, self.height)
+    }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Battlesnake {
-    id: String,
-    name: String,
-    health: i32,
-    body: Vec<Coord>,
-    head: Coord,
-    length: i32,
-    latency: String,
-    shout: Option<String>,
+    pub id: String,
+    pub name: String,
+    pub health: i32,
+    pub body: Vec<Coord>,
+    pub head: Coord,
+    pub length: i32,
+    pub latency: String,
+    pub shout: Option<String>,
+    /// The team this snake plays for in Squad games
+    #[serde(default)]
+    pub squad: String,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Coord {
-    x: i32,
-    y: i32,
+    pub x: i32,
+    pub y: i32,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GameState {
     pub game: Game,
     pub turn: i32,
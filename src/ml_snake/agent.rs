@@ -0,0 +1,47 @@
+use crate::{
+    ml_snake::logic::choose_move, neural_network::NeuralNetwork, simulation::rules::Direction,
+    Battlesnake, Board, Game,
+};
+
+/// Something that can play a snake, such as a network, a scripted bot or a search.
+/// The server and the simulator both drive agents through these hooks
+pub trait Agent {
+    /// Called once when a game begins
+    fn start(&mut self, _game: &Game, _turn: &i32, _board: &Board, _me: &Battlesnake) {}
+
+    /// The move to make this turn. `None` leaves the snake to repeat its last move,
+    /// as the engine does for snakes that don't answer
+    fn get_move(
+        &mut self,
+        game: &Game,
+        turn: &i32,
+        board: &Board,
+        me: &Battlesnake,
+    ) -> Option<Direction>;
+
+    /// Called once when the game is over, with the snake as it last was
+    fn end(&mut self, _game: &Game, _turn: &i32, _board: &Board, _me: &Battlesnake) {}
+}
+
+/// Plays with a neural network, through the move safety mask
+pub struct NeuralNetworkAgent {
+    pub network: NeuralNetwork,
+}
+
+impl NeuralNetworkAgent {
+    pub fn new(network: NeuralNetwork) -> Self {
+        Self { network }
+    }
+}
+
+impl Agent for NeuralNetworkAgent {
+    fn get_move(
+        &mut self,
+        game: &Game,
+        turn: &i32,
+        board: &Board,
+        me: &Battlesnake,
+    ) -> Option<Direction> {
+        choose_move(game, turn, board, me, &mut self.network).and_then(Direction::from_name)
+    }
+}
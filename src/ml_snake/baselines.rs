@@ -0,0 +1,228 @@
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    geometry::Geometry,
+    ml_snake::{
+        agent::Agent,
+        safety::MoveMask,
+        space::{SpaceAgent, SpaceAnalysis},
+    },
+    simulation::rules::{Direction, GameMode, Ruleset},
+    Battlesnake, Board, Coord, Game,
+};
+
+/// The scripted opponents networks are measured against. Each plays deterministically, the random
+/// one from its seed, and never makes a less safe move than it has to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Baseline {
+    /// Any of the safest moves, at random
+    RandomSafe,
+    /// Towards the closest food
+    GreedyFood,
+    /// Towards its own tail, circling in place
+    TailChaser,
+    /// Into the move with the most room to move around in
+    FloodFill,
+    /// Towards the heads of shorter opponents to win head-ons, eating until it is the longest
+    HeadHunter,
+    /// Towards the closest food without trapping itself
+    Space,
+}
+
+impl Baseline {
+    pub const ALL: [Baseline; 6] = [
+        Baseline::RandomSafe,
+        Baseline::GreedyFood,
+        Baseline::TailChaser,
+        Baseline::FloodFill,
+        Baseline::HeadHunter,
+        Baseline::Space,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Baseline::RandomSafe => "random_safe",
+            Baseline::GreedyFood => "greedy_food",
+            Baseline::TailChaser => "tail_chaser",
+            Baseline::FloodFill => "flood_fill",
+            Baseline::HeadHunter => "head_hunter",
+            Baseline::Space => "space",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "random_safe" => Some(Baseline::RandomSafe),
+            "greedy_food" => Some(Baseline::GreedyFood),
+            "tail_chaser" => Some(Baseline::TailChaser),
+            "flood_fill" => Some(Baseline::FloodFill),
+            "head_hunter" => Some(Baseline::HeadHunter),
+            "space" => Some(Baseline::Space),
+            _ => None,
+        }
+    }
+
+    /// A new agent playing this baseline. Only the random one uses `seed`
+    pub fn agent(&self, seed: u64) -> Box<dyn Agent + Send> {
+        match self {
+            Baseline::RandomSafe => Box::new(RandomSafeAgent::new(seed)),
+            Baseline::GreedyFood => Box::new(GreedyFoodAgent),
+            Baseline::TailChaser => Box::new(TailChaserAgent),
+            Baseline::FloodFill => Box::new(FloodFillAgent),
+            Baseline::HeadHunter => Box::new(HeadHunterAgent),
+            Baseline::Space => Box::new(SpaceAgent),
+        }
+    }
+}
+
+pub struct RandomSafeAgent {
+    rng: StdRng,
+}
+
+impl RandomSafeAgent {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Agent for RandomSafeAgent {
+    fn get_move(
+        &mut self,
+        game: &Game,
+        _turn: &i32,
+        board: &Board,
+        me: &Battlesnake,
+    ) -> Option<Direction> {
+        MoveMask::new(game, board, me)
+            .allowed_moves()
+            .choose(&mut self.rng)
+            .copied()
+    }
+}
+
+pub struct GreedyFoodAgent;
+
+impl Agent for GreedyFoodAgent {
+    fn get_move(
+        &mut self,
+        game: &Game,
+        _turn: &i32,
+        board: &Board,
+        me: &Battlesnake,
+    ) -> Option<Direction> {
+        choose_closest(game, board, me, &board.food)
+    }
+}
+
+pub struct TailChaserAgent;
+
+impl Agent for TailChaserAgent {
+    fn get_move(
+        &mut self,
+        game: &Game,
+        _turn: &i32,
+        board: &Board,
+        me: &Battlesnake,
+    ) -> Option<Direction> {
+        let tail = me.body.last().copied().unwrap_or(me.head);
+
+        choose_closest(game, board, me, &[tail])
+    }
+}
+
+pub struct FloodFillAgent;
+
+impl Agent for FloodFillAgent {
+    fn get_move(
+        &mut self,
+        game: &Game,
+        _turn: &i32,
+        board: &Board,
+        me: &Battlesnake,
+    ) -> Option<Direction> {
+        let space_analysis = SpaceAnalysis::new(game, board, me);
+
+        let scores: Vec<(Direction, f32)> = space_analysis
+            .moves
+            .iter()
+            .map(|move_space| (move_space.direction, move_space.area as f32))
+            .collect();
+
+        MoveMask::new(game, board, me).choose(&scores)
+    }
+}
+
+pub struct HeadHunterAgent;
+
+impl Agent for HeadHunterAgent {
+    fn get_move(
+        &mut self,
+        game: &Game,
+        _turn: &i32,
+        board: &Board,
+        me: &Battlesnake,
+    ) -> Option<Direction> {
+        let ruleset = Ruleset::from_game(game);
+
+        let targets: Vec<Coord> = board
+            .snakes
+            .iter()
+            .filter(|snake| snake.id != me.id && snake.length < me.length)
+            .filter(|snake| !(ruleset.mode == GameMode::Squad && snake.squad == me.squad))
+            .map(|snake| snake.head)
+            .collect();
+
+        // With nobody shorter to hunt, grow until there is
+
+        if targets.is_empty() {
+            return choose_closest(game, board, me, &board.food);
+        }
+
+        choose_closest(game, board, me, &targets)
+    }
+}
+
+/// The board's geometry, wrapping when the ruleset does
+fn geometry(ruleset: &Ruleset, board: &Board) -> Geometry {
+    board
+        .geometry()
+        .with_wrapping(ruleset.mode == GameMode::Wrapped)
+}
+
+/// The safest move bringing `me` closest to any of `targets`. Without targets, the first safest move
+fn choose_closest(
+    game: &Game,
+    board: &Board,
+    me: &Battlesnake,
+    targets: &[Coord],
+) -> Option<Direction> {
+    let geometry = geometry(&Ruleset::from_game(game), board);
+
+    choose_by_score(game, board, me, |coord| {
+        targets
+            .iter()
+            .map(|target| -geometry.distance(coord, *target) as f32)
+            .max_by(|a, b| a.total_cmp(b))
+            .unwrap_or(0.)
+    })
+}
+
+/// The safest move with the highest score for the cell it moves into. Ties go to the move
+/// listed first in `Direction::ALL`, so choices depend on nothing but the board
+fn choose_by_score(
+    game: &Game,
+    board: &Board,
+    me: &Battlesnake,
+    score: impl Fn(Coord) -> f32,
+) -> Option<Direction> {
+    let geometry = geometry(&Ruleset::from_game(game), board);
+
+    let scores: Vec<(Direction, f32)> = geometry
+        .neighbors(me.head)
+        .map(|(direction, coord)| (direction, score(coord)))
+        .collect();
+
+    MoveMask::new(game, board, me).choose(&scores)
+}
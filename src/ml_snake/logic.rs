@@ -11,13 +11,14 @@
 // For more info see docs.battlesnake.com
 
 use log::info;
-use rand::seq::SliceRandom;
 use serde_json::{json, Value};
-use std::{collections::HashMap, time::SystemTime};
+use std::time::SystemTime;
 
 use crate::{
-    neural_network::{Input, NeuralNetwork, NeuralNetworkManager, Output},
-    utils::{bool_as_f32, build_neural_network, pack_coord},
+    neural_network::{Input, NeuralNetwork},
+    ml_snake::{safety::MoveMask, space::SpaceAnalysis, territory::Territory},
+    simulation::rules::Direction,
+    utils::bool_as_f32,
     Battlesnake, Board, Game,
 };
 
@@ -27,13 +28,13 @@ use crate::{
 pub fn info() -> Value {
     info!("INFO");
 
-    return json!({
+    json!({
         "apiversion": "1",
         "author": "MarvinTMB", // TODO: Your Battlesnake Username
         "color": "#926ee4", // TODO: Choose color
         "head": "safe", // TODO: Choose head
         "tail": "bolt", // TODO: Choose tail
-    });
+    })
 }
 
 // start is called when your Battlesnake begins a game
@@ -63,25 +64,18 @@ pub struct CoordInfo {
     pub my_body: bool,
     pub opponent_head: bool,
     pub opponent_body: bool,
+    pub hazard: bool,
+    /// 1 when we reach the cell before any opponent, -1 when an opponent does and 0 when nobody does
+    pub territory: f32,
 }
 
-// move is called on every turn and returns your next move
-// Valid moves are "up", "down", "left", or "right"
-// See https://docs.battlesnake.com/api/example-move for available data
-pub fn get_move<'a>(game: &Game, turn: &i32, board: &Board, me: &Battlesnake) -> Option<&'a str> {
-    let mut neural_network_manager = NeuralNetworkManager::new();
-    let mut neural_network = NeuralNetwork::new(&mut neural_network_manager);
-
-    build_neural_network(&mut neural_network, board.width, board.height);
-    neural_network.mutate();
-
-    choose_move(game, turn, board, me, &mut neural_network)
+// move_scores is how much the network likes each move, in `Direction::ALL` order, without regard for safety
+pub fn move_scores(game: &Game, board: &Board, me: &Battlesnake, neural_network: &mut NeuralNetwork) -> Vec<(Direction, f32)> {
+    policy_value(game, board, me, neural_network).0
 }
 
-pub fn choose_move<'a>(_game: &Game, _turn: &i32, board: &Board, me: &Battlesnake, neural_network: &mut NeuralNetwork) -> Option<&'a str> {
-    #[cfg(feature = "benchmark")]
-    let start = SystemTime::now();
-
+// policy_value is the network's move scores along with its value output, how good it thinks the board is for us
+pub fn policy_value(game: &Game, board: &Board, me: &Battlesnake, neural_network: &mut NeuralNetwork) -> (Vec<(Direction, f32)>, f32) {
     let mut game_info = GameInfo {
         my_health: me.health,
         my_length: me.length,
@@ -89,26 +83,22 @@ pub fn choose_move<'a>(_game: &Game, _turn: &i32, board: &Board, me: &Battlesnak
         opponent_lengths: Vec::new(),
     };
 
+    let geometry = board.geometry();
     let mut grid: Vec<CoordInfo> = Vec::new();
 
-    // let board_width = &board.width;
-    // let board_height = &board.height;
-
-    for x in 0..board.width {
-        for y in 0..board.height {
-            grid.push(CoordInfo {
-                x,
-                y: y as i32,
-                ..Default::default()
-            });
-        }
+    for coord in geometry.coords() {
+        grid.push(CoordInfo {
+            x: coord.x,
+            y: coord.y,
+            ..Default::default()
+        });
     }
 
     for any_snake in &board.snakes {
         game_info.opponent_healths.push(any_snake.health);
         game_info.opponent_lengths.push(any_snake.length);
 
-        if let Some(coord_info) = grid.get_mut(pack_coord(&any_snake.head, board.width) as usize) {
+        if let Some(coord_info) = grid.get_mut(geometry.pack(any_snake.head)) {
             if any_snake.id == me.id {
                 coord_info.my_head = true;
             } else {
@@ -117,7 +107,7 @@ pub fn choose_move<'a>(_game: &Game, _turn: &i32, board: &Board, me: &Battlesnak
         };
 
         for body_part in &any_snake.body {
-            if let Some(coord_info) = grid.get_mut(pack_coord(body_part, board.width) as usize) {
+            if let Some(coord_info) = grid.get_mut(geometry.pack(*body_part)) {
                 if any_snake.id == me.id {
                     coord_info.my_body = true;
                 } else {
@@ -128,11 +118,23 @@ pub fn choose_move<'a>(_game: &Game, _turn: &i32, board: &Board, me: &Battlesnak
     }
 
     for food_coord in &board.food {
-        if let Some(coord_info) = grid.get_mut(pack_coord(&food_coord, board.width) as usize) {
+        if let Some(coord_info) = grid.get_mut(geometry.pack(*food_coord)) {
             coord_info.food = true;
         };
     }
 
+    for hazard_coord in &board.hazards {
+        if let Some(coord_info) = grid.get_mut(geometry.pack(*hazard_coord)) {
+            coord_info.hazard = true;
+        };
+    }
+
+    let territory = Territory::new(game, board).plane(&me.id);
+
+    for (coord_info, territory) in grid.iter_mut().zip(territory) {
+        coord_info.territory = territory;
+    }
+
     // neural network
 
     let mut inputs: Vec<Input> = vec![Input::new(
@@ -146,6 +148,22 @@ pub fn choose_move<'a>(_game: &Game, _turn: &i32, board: &Board, me: &Battlesnak
         ],
     )];
 
+    // How much room each move leaves, in the same order as the outputs
+
+    let space_analysis = SpaceAnalysis::new(game, board, me);
+
+    for move_space in &space_analysis.moves {
+        inputs.push(Input::new(
+            "space".to_string(),
+            vec![
+                move_space.area as f32 / geometry.area() as f32,
+                bool_as_f32(move_space.reaches_tail),
+                move_space.area_per_length,
+            ],
+            vec!["s0".to_string(), "s1".to_string(), "s2".to_string()],
+        ))
+    }
+
     for coord_info in &grid {
         inputs.push(Input::new(
             "coord".to_string(),
@@ -159,6 +177,8 @@ pub fn choose_move<'a>(_game: &Game, _turn: &i32, board: &Board, me: &Battlesnak
                 bool_as_f32(coord_info.my_body),
                 bool_as_f32(coord_info.opponent_head),
                 bool_as_f32(coord_info.opponent_body),
+                bool_as_f32(coord_info.hazard),
+                coord_info.territory,
             ],
             vec![
                 "c0".to_string(),
@@ -168,107 +188,58 @@ pub fn choose_move<'a>(_game: &Game, _turn: &i32, board: &Board, me: &Battlesnak
                 "c4".to_string(),
                 "c5".to_string(),
                 "c6".to_string(),
+                "c7".to_string(),
+                "c8".to_string(),
             ],
         ))
     }
 
-    let outputs: Vec<Output> = vec![
-        Output::new("up".to_string()),
-        Output::new("down".to_string()),
-        Output::new("left".to_string()),
-        Output::new("right".to_string()),
-    ];
-
     neural_network.forward_propagate(&inputs);
     let outputs = neural_network.get_outputs();
 
-    let move_options = vec![
-        ("up", outputs[0]),
-        ("down", outputs[1]),
-        ("left", outputs[2]),
-        ("right", outputs[3]),
+    let move_scores = vec![
+        (Direction::Up, outputs[0]),
+        (Direction::Down, outputs[1]),
+        (Direction::Left, outputs[2]),
+        (Direction::Right, outputs[3]),
     ];
 
-    println!("network {:#?}", neural_network);
+    (move_scores, outputs[4])
+}
 
-    let mut chosen_move: Option<&str> = None;
-    let mut best_score = -1.0;
+// choose_move is called on every turn by the neural network agent and returns its next move
+// Valid moves are "up", "down", "left", or "right"
+// See https://docs.battlesnake.com/api/example-move for available data
+pub fn choose_move<'a>(
+    game: &Game,
+    _turn: &i32,
+    board: &Board,
+    me: &Battlesnake,
+    neural_network: &mut NeuralNetwork,
+) -> Option<&'a str> {
+    #[cfg(feature = "benchmark")]
+    let start = SystemTime::now();
 
-    for (move_name, move_score) in move_options {
+    let move_options = move_scores(game, board, me, neural_network);
 
-        if move_score <= best_score {
-            continue;
-        }
+    // Only consider the safest moves available, so the network can't pick one that is sure to lose
 
-        chosen_move = Some(move_name);
-        best_score = move_score;
-    }
+    let move_mask = MoveMask::new(game, board, me);
+    let chosen_move = move_mask.choose(&move_options)?;
 
-    let Some(chosen_move) = chosen_move else {
-        return None// "up"
-    };
+    #[cfg(feature = "snake_logs")]
+    let best_score = move_options
+        .iter()
+        .find(|(direction, _)| *direction == chosen_move)
+        .map_or(0., |(_, score)| *score);
 
     #[cfg(feature = "snake_logs")]
-    println!("MOVE {} with score {}", chosen_move, best_score);
-
-    //
-
-    // let mut is_move_safe: HashMap<_, _> = vec![
-    //     ("up", true),
-    //     ("down", true),
-    //     ("left", true),
-    //     ("right", true),
-    // ]
-    // .into_iter()
-    // .collect();
-
-    // // We've included code to prevent your Battlesnake from moving backwards
-    // let my_head = &me.body[0]; // Coordinates of your head
-    // let my_neck = &me.body[1]; // Coordinates of your "neck"
-
-    // if my_neck.x < my_head.x {
-    //     // Neck is left of head, don't move left
-    //     is_move_safe.insert("left", false);
-    // } else if my_neck.x > my_head.x {
-    //     // Neck is right of head, don't move right
-    //     is_move_safe.insert("right", false);
-    // } else if my_neck.y < my_head.y {
-    //     // Neck is below head, don't move down
-    //     is_move_safe.insert("down", false);
-    // } else if my_neck.y > my_head.y {
-    //     // Neck is above head, don't move up
-    //     is_move_safe.insert("up", false);
-    // }
-
-    // // TODO: Step 1 - Prevent your Battlesnake from moving out of bounds
-    // // let board_width = &board.width;
-    // // let board_height = &board.height;
-
-    // // TODO: Step 2 - Prevent your Battlesnake from colliding with itself
-    // // let my_body = &you.body;
-
-    // // TODO: Step 3 - Prevent your Battlesnake from colliding with other Battlesnakes
-    // // let opponents = &board.snakes;
-
-    // // Are there any safe moves left?
-    // let safe_moves = is_move_safe
-    //     .into_iter()
-    //     .filter(|&(_, v)| v)
-    //     .map(|(k, _)| k)
-    //     .collect::<Vec<_>>();
-
-    // // Choose a random move from the safe ones
-    // let chosen = safe_moves.choose(&mut rand::thread_rng()).unwrap();
-
-    // // TODO: Step 4 - Move towards food instead of random, to regain health and survive longer
-    // // let food = &board.food;
-
-    // info!("MOVE {}: {}", turn, chosen);
+    println!("MOVE {} with score {}", chosen_move.name(), best_score);
 
     #[cfg(feature = "benchmark")]
     let duration = SystemTime::now().duration_since(start).unwrap().as_millis();
     #[cfg(feature = "benchmark")]
     info!("took {}ms", duration);
 
-    return Some(chosen_move);
+    Some(chosen_move.name())
 }
\ No newline at end of file
@@ -0,0 +1,375 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    constants::{DEFAULT_EXPLORATION, HEURISTIC_VALUE_SCALE, SEARCH_LATENCY_MARGIN_MS},
+    ml_snake::{
+        agent::Agent,
+        search::{
+            candidate_moves, deadline, search_or_fallback, Evaluator, HeuristicEvaluator,
+            PolicyValue,
+        },
+    },
+    neural_network::NeuralNetwork,
+    simulation::{
+        make_move::{make_move, unmake_move, Unmake},
+        rules::{Direction, Ruleset},
+    },
+    Battlesnake, Board, Game,
+};
+
+/// What a tree search settled on
+#[derive(Debug, Clone, PartialEq)]
+pub struct MctsResult {
+    /// Our most visited move
+    pub best_move: Direction,
+    /// How often each of our moves was tried at the root, usable as a training target for the policy
+    pub visits: Vec<(Direction, u32)>,
+    pub iterations: u32,
+}
+
+/// Monte Carlo tree search for any number of snakes. Moves are simultaneous, so it uses decoupled
+/// UCT: every snake picks its own move at each node from its own statistics, as if it didn't know
+/// the others', and the joint move leads to the next node.
+///
+/// With a network, moves are tried in proportion to its move scores and new positions are valued
+/// by its value output, AlphaZero-style. Without one, moves start out equally likely and
+/// positions are valued by a `HeuristicEvaluator`
+pub struct MctsAgent {
+    /// Usually a `NeuralNetwork`, though anything with move scores and a value will do
+    pub network: Option<Box<dyn PolicyValue + Send>>,
+    pub heuristic: HeuristicEvaluator,
+    /// How much to favour moves that haven't been tried much over those that have scored well
+    pub exploration: f32,
+    /// Time kept back from `Game.timeout` for the round trip. Tree search grows a position at a
+    /// time rather than a turn deeper, so it has no use for the rest of `SearchLimits`
+    pub latency_margin: Duration,
+    /// Stops after this many iterations even with time left, such as to make a search repeatable
+    pub max_iterations: Option<u32>,
+}
+
+impl MctsAgent {
+    pub fn new(network: Option<NeuralNetwork>) -> Self {
+        Self {
+            network: network.map(|network| Box::new(network) as Box<dyn PolicyValue + Send>),
+            heuristic: HeuristicEvaluator::default(),
+            exploration: DEFAULT_EXPLORATION,
+            latency_margin: Duration::from_millis(SEARCH_LATENCY_MARGIN_MS),
+            max_iterations: None,
+        }
+    }
+
+    /// Grows the tree until the time budget or `max_iterations` runs out. `None` when the game is
+    /// already decided, with nobody left to play against, or there wasn't time for a single
+    /// iteration
+    pub fn search(&mut self, game: &Game, board: &Board, me: &Battlesnake) -> Option<MctsResult> {
+        let deadline = deadline(game, self.latency_margin);
+
+        let mut tree = Tree {
+            game,
+            ruleset: Ruleset::from_game(game),
+            starting_snakes_count: board.snakes.len(),
+            nodes: vec![Node::default()],
+            agent: self,
+        };
+
+        let mut board = board.clone();
+
+        if tree.is_over(&board) {
+            return None;
+        }
+
+        tree.expand(0, &board);
+
+        let mut iterations = 0;
+
+        while Instant::now() < deadline
+            && tree
+                .agent
+                .max_iterations
+                .is_none_or(|max_iterations| iterations < max_iterations)
+        {
+            tree.iterate(&mut board);
+            iterations += 1;
+        }
+
+        // Without any visits every move looks the same, so leave the choice to the fallback
+
+        if iterations == 0 {
+            return None;
+        }
+
+        let Some(snake_stats) = tree.nodes[0]
+            .snakes
+            .iter()
+            .find(|snake_stats| snake_stats.id == me.id)
+        else {
+            panic!("no statistics for snake {}", me.id);
+        };
+
+        let visits: Vec<(Direction, u32)> = snake_stats
+            .moves
+            .iter()
+            .map(|move_stats| (move_stats.direction, move_stats.visits))
+            .collect();
+
+        // `max_by_key` keeps the last of equals, so going backwards breaks ties for the first candidate
+
+        let (best_move, _) = visits
+            .iter()
+            .rev()
+            .copied()
+            .max_by_key(|(_, visits)| *visits)?;
+
+        Some(MctsResult {
+            best_move,
+            visits,
+            iterations,
+        })
+    }
+}
+
+impl Agent for MctsAgent {
+    fn get_move(
+        &mut self,
+        game: &Game,
+        turn: &i32,
+        board: &Board,
+        me: &Battlesnake,
+    ) -> Option<Direction> {
+        let searched_move = self.search(game, board, me).map(|result| result.best_move);
+
+        search_or_fallback(searched_move, game, turn, board, me)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MoveStats {
+    direction: Direction,
+    prior: f32,
+    visits: u32,
+    total_value: f32,
+}
+
+/// One snake's choices at a node
+#[derive(Debug, Clone)]
+struct SnakeStats {
+    id: String,
+    moves: Vec<MoveStats>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    /// One per snake on the board here, in the same order. Empty until the node is expanded
+    snakes: Vec<SnakeStats>,
+    /// The node each joint move leads to, with moves in the same order as `snakes`
+    children: HashMap<Vec<Direction>, usize>,
+    visits: u32,
+}
+
+/// The state shared by every iteration of one search
+struct Tree<'a> {
+    game: &'a Game,
+    ruleset: Ruleset,
+    starting_snakes_count: usize,
+    nodes: Vec<Node>,
+    agent: &'a mut MctsAgent,
+}
+
+impl Tree<'_> {
+    /// Walks down from the root by each snake's best move until reaching a node that hasn't been
+    /// expanded or a finished game, values it, then adds the value to every move on the way.
+    /// Leaves `board` as it found it
+    fn iterate(&mut self, board: &mut Board) {
+        let mut path: Vec<(usize, Vec<usize>)> = Vec::new();
+        let mut unmakes: Vec<Unmake> = Vec::new();
+        let mut node_index = 0;
+
+        let values = loop {
+            if self.is_over(board) {
+                break self.outcome_values(board);
+            }
+
+            if self.nodes[node_index].snakes.is_empty() {
+                break self.expand(node_index, board);
+            }
+
+            let choices = self.select(node_index);
+            let moves: Vec<Direction> = self.nodes[node_index]
+                .snakes
+                .iter()
+                .zip(&choices)
+                .map(|(snake_stats, choice)| snake_stats.moves[*choice].direction)
+                .collect();
+
+            unmakes.push(make_move(board, &self.ruleset, &moves));
+            path.push((node_index, choices));
+
+            let next_index = self.nodes.len();
+            node_index = *self.nodes[node_index]
+                .children
+                .entry(moves)
+                .or_insert(next_index);
+
+            if node_index == next_index {
+                self.nodes.push(Node::default());
+            }
+        };
+
+        // Snakes eliminated on the way down have no value, which counts as a loss
+
+        for (node_index, choices) in path {
+            let node = &mut self.nodes[node_index];
+            node.visits += 1;
+
+            for (snake_stats, choice) in node.snakes.iter_mut().zip(choices) {
+                let move_stats = &mut snake_stats.moves[choice];
+
+                move_stats.visits += 1;
+                move_stats.total_value += values.get(&snake_stats.id).copied().unwrap_or(0.);
+            }
+        }
+
+        for unmake in unmakes.into_iter().rev() {
+            unmake_move(board, unmake);
+        }
+    }
+
+    /// Each snake's move at a node by PUCT: its average value so far, plus a bonus for moves
+    /// its prior favours that haven't been tried much
+    fn select(&self, node_index: usize) -> Vec<usize> {
+        let node = &self.nodes[node_index];
+        let exploration = self.agent.exploration * ((node.visits + 1) as f32).sqrt();
+
+        node.snakes
+            .iter()
+            .map(|snake_stats| {
+                let scores = snake_stats.moves.iter().map(|move_stats| {
+                    let average_value = if move_stats.visits == 0 {
+                        0.5
+                    } else {
+                        move_stats.total_value / move_stats.visits as f32
+                    };
+
+                    average_value + exploration * move_stats.prior / (1 + move_stats.visits) as f32
+                });
+
+                // `max_by` keeps the last of equals, so going backwards breaks ties for the first move
+
+                scores
+                    .enumerate()
+                    .rev()
+                    .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map_or(0, |(choice, _)| choice)
+            })
+            .collect()
+    }
+
+    /// Fills in a node's choices for every snake on `board`, returning how good the board is for
+    /// each, from 0 for a sure loss to 1 for a sure win
+    fn expand(&mut self, node_index: usize, board: &Board) -> HashMap<String, f32> {
+        let mut values = HashMap::new();
+        let mut snakes = Vec::new();
+
+        // Heuristic scores are only meaningful compared with each other, so each snake is valued
+        // by how far ahead it is of the best of the others
+
+        let heuristic_scores: Vec<f32> = match self.agent.network {
+            Some(_) => Vec::new(),
+            None => board
+                .snakes
+                .iter()
+                .map(|snake| self.agent.heuristic.evaluate(self.game, board, snake))
+                .collect(),
+        };
+
+        for (i, snake) in board.snakes.iter().enumerate() {
+            let candidates = candidate_moves(self.game, board, snake);
+
+            let (priors, value) = match &mut self.agent.network {
+                Some(network) => {
+                    let (move_scores, value) = network.policy_value(self.game, board, snake);
+
+                    let scores: Vec<f32> = candidates
+                        .iter()
+                        .map(|direction| {
+                            move_scores
+                                .iter()
+                                .find(|(other, _)| other == direction)
+                                .map_or(0., |(_, score)| *score)
+                        })
+                        .collect();
+
+                    (softmax(&scores), sigmoid(value))
+                }
+                None => {
+                    let best_other = heuristic_scores
+                        .iter()
+                        .enumerate()
+                        .filter(|(j, _)| *j != i)
+                        .map(|(_, score)| *score)
+                        .max_by(|a, b| a.total_cmp(b))
+                        .unwrap_or(0.);
+
+                    (
+                        vec![1. / candidates.len() as f32; candidates.len()],
+                        sigmoid((heuristic_scores[i] - best_other) / HEURISTIC_VALUE_SCALE),
+                    )
+                }
+            };
+
+            values.insert(snake.id.clone(), value);
+            snakes.push(SnakeStats {
+                id: snake.id.clone(),
+                moves: candidates
+                    .into_iter()
+                    .zip(priors)
+                    .map(|(direction, prior)| MoveStats {
+                        direction,
+                        prior,
+                        visits: 0,
+                        total_value: 0.,
+                    })
+                    .collect(),
+            });
+        }
+
+        let node = &mut self.nodes[node_index];
+        node.snakes = snakes;
+        node.visits = 1;
+
+        values
+    }
+
+    /// Whether the game is over: a single snake is left out of several, or none at all
+    fn is_over(&self, board: &Board) -> bool {
+        board.snakes.is_empty() || (self.starting_snakes_count > 1 && board.snakes.len() == 1)
+    }
+
+    /// The value of a finished game: a win for the snake left standing. Snakes that were
+    /// eliminated, including together, have lost
+    fn outcome_values(&self, board: &Board) -> HashMap<String, f32> {
+        board
+            .snakes
+            .iter()
+            .map(|snake| (snake.id.clone(), 1.))
+            .collect()
+    }
+}
+
+fn sigmoid(value: f32) -> f32 {
+    1. / (1. + (-value).exp())
+}
+
+fn softmax(scores: &[f32]) -> Vec<f32> {
+    let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let exponentials: Vec<f32> = scores.iter().map(|score| (score - max).exp()).collect();
+    let sum: f32 = exponentials.iter().sum();
+
+    exponentials
+        .into_iter()
+        .map(|exponential| exponential / sum)
+        .collect()
+}
@@ -0,0 +1,276 @@
+use std::time::Instant;
+
+use crate::{
+    constants::WIN_SCORE,
+    ml_snake::{
+        agent::Agent,
+        search::{candidate_moves, joint_moves, search_or_fallback, Evaluator, SearchLimits},
+    },
+    simulation::{
+        make_move::{make_move, unmake_move},
+        rules::{Direction, Ruleset},
+    },
+    Battlesnake, Board, Game,
+};
+
+/// What a search settled on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchResult {
+    pub best_move: Direction,
+    /// The best move's score, from `WIN_SCORE` for a sure win to `-WIN_SCORE` for a sure loss
+    pub score: f32,
+    /// The number of turns looked ahead by the deepest search that finished
+    pub depth: u32,
+}
+
+/// Looks ahead in duels with iterative-deepening alpha-beta through the simulator's rules.
+/// Moves are simultaneous, so each turn is searched as our move followed by the opponent's reply
+/// to it, assuming the opponent knows our move. Games without exactly one opponent are left to
+/// `SpaceAgent`
+pub struct AlphaBetaAgent<E: Evaluator> {
+    pub evaluator: E,
+    pub limits: SearchLimits,
+}
+
+impl<E: Evaluator> AlphaBetaAgent<E> {
+    pub fn new(evaluator: E) -> Self {
+        Self {
+            evaluator,
+            limits: SearchLimits::default(),
+        }
+    }
+
+    /// Searches one turn deeper at a time until `limits` run out or the outcome is decided. `None`
+    /// without exactly one opponent, or if not even one turn could be searched in time
+    pub fn search(&mut self, game: &Game, board: &Board, me: &Battlesnake) -> Option<SearchResult> {
+        if board.snakes.len() != 2 {
+            return None;
+        }
+
+        paranoid_search(
+            game,
+            board,
+            me,
+            &mut self.evaluator,
+            self.limits.max_depth,
+            self.limits.deadline(game),
+        )
+    }
+}
+
+impl<E: Evaluator> Agent for AlphaBetaAgent<E> {
+    fn get_move(
+        &mut self,
+        game: &Game,
+        turn: &i32,
+        board: &Board,
+        me: &Battlesnake,
+    ) -> Option<Direction> {
+        let searched_move = self.search(game, board, me).map(|result| result.best_move);
+
+        search_or_fallback(searched_move, game, turn, board, me)
+    }
+}
+
+/// Iterative-deepening alpha-beta for `me` against every opponent at once. Each turn is searched as
+/// our move followed by the opponents' joint reply to it, chosen to do us the most harm.
+/// `None` if not even one turn could be searched by `deadline`
+pub(crate) fn paranoid_search<E: Evaluator>(
+    game: &Game,
+    board: &Board,
+    me: &Battlesnake,
+    evaluator: &mut E,
+    max_depth: u32,
+    deadline: Instant,
+) -> Option<SearchResult> {
+    let mut search = Search {
+        game,
+        ruleset: Ruleset::from_game(game),
+        me: &me.id,
+        evaluator,
+        deadline,
+    };
+
+    let mut board = board.clone();
+    let mut my_moves = candidate_moves(game, &board, me);
+    let mut result = None;
+
+    for depth in 1..=max_depth {
+        let Some((best_move, score)) = search.root(&mut board, depth, &my_moves) else {
+            break;
+        };
+
+        result = Some(SearchResult {
+            best_move,
+            score,
+            depth,
+        });
+
+        // The next search looks at this one's best move first, to cut more of the others
+
+        my_moves.retain(|direction| *direction != best_move);
+        my_moves.insert(0, best_move);
+
+        if score.abs() >= WIN_SCORE / 2. {
+            break;
+        }
+    }
+
+    result
+}
+
+/// The state shared by every node of one search
+struct Search<'a, E: Evaluator> {
+    game: &'a Game,
+    ruleset: Ruleset,
+    me: &'a str,
+    evaluator: &'a mut E,
+    deadline: Instant,
+}
+
+impl<E: Evaluator> Search<'_, E> {
+    /// The best of `my_moves` and its score, searched `depth` turns deep. `None` when out of time
+    fn root(
+        &mut self,
+        board: &mut Board,
+        depth: u32,
+        my_moves: &[Direction],
+    ) -> Option<(Direction, f32)> {
+        let opponent_moves = self.opponent_moves(board);
+        let mut best: Option<(Direction, f32)> = None;
+
+        for my_move in my_moves {
+            let alpha = best.map_or(f32::NEG_INFINITY, |(_, score)| score);
+            let score = self.reply(
+                board,
+                *my_move,
+                &opponent_moves,
+                depth,
+                0,
+                alpha,
+                f32::INFINITY,
+            )?;
+
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((*my_move, score));
+            }
+        }
+
+        best
+    }
+
+    /// The score of the position for us, searched `depth` turns deep, `ply` turns from the root.
+    /// `None` when out of time
+    fn alpha_beta(
+        &mut self,
+        board: &mut Board,
+        depth: u32,
+        ply: u32,
+        alpha: f32,
+        beta: f32,
+    ) -> Option<f32> {
+        let me = board.snakes.iter().find(|snake| snake.id == self.me);
+        let has_opponents = board.snakes.iter().any(|snake| snake.id != self.me);
+
+        match (me, has_opponents) {
+            (None, false) => return Some(0.),
+            (None, true) => return Some(-WIN_SCORE + ply as f32),
+            (Some(_), false) => return Some(WIN_SCORE - ply as f32),
+            (Some(me), true) if depth == 0 => {
+                return Some(self.evaluator.evaluate(self.game, board, me))
+            }
+            _ => {}
+        }
+
+        if Instant::now() >= self.deadline {
+            return None;
+        }
+
+        let Some(me) = me else {
+            panic!("no snake {}", self.me);
+        };
+
+        let my_moves = candidate_moves(self.game, board, me);
+        let opponent_moves = self.opponent_moves(board);
+        let mut best = f32::NEG_INFINITY;
+
+        for my_move in my_moves {
+            let score = self.reply(
+                board,
+                my_move,
+                &opponent_moves,
+                depth,
+                ply,
+                alpha.max(best),
+                beta,
+            )?;
+
+            best = best.max(score);
+
+            if best >= beta {
+                break;
+            }
+        }
+
+        Some(best)
+    }
+
+    /// The score of `my_move` against the opponents' most harmful joint reply
+    #[allow(clippy::too_many_arguments)]
+    fn reply(
+        &mut self,
+        board: &mut Board,
+        my_move: Direction,
+        opponent_moves: &[Vec<Direction>],
+        depth: u32,
+        ply: u32,
+        alpha: f32,
+        beta: f32,
+    ) -> Option<f32> {
+        let mut worst = f32::INFINITY;
+
+        for joint_move in opponent_moves {
+            let mut joint_move = joint_move.iter();
+
+            let moves: Vec<Direction> = board
+                .snakes
+                .iter()
+                .map(|snake| {
+                    if snake.id == self.me {
+                        return my_move;
+                    }
+
+                    let Some(direction) = joint_move.next() else {
+                        panic!("no move for snake {}", snake.id);
+                    };
+
+                    *direction
+                })
+                .collect();
+
+            let unmake = make_move(board, &self.ruleset, &moves);
+            let score = self.alpha_beta(board, depth - 1, ply + 1, alpha, beta.min(worst));
+            unmake_move(board, unmake);
+
+            worst = worst.min(score?);
+
+            if worst <= alpha {
+                break;
+            }
+        }
+
+        Some(worst)
+    }
+
+    /// Every combination of the opponents' moves, each in the same order as `board.snakes`
+    fn opponent_moves(&self, board: &Board) -> Vec<Vec<Direction>> {
+        let moves: Vec<Vec<Direction>> = board
+            .snakes
+            .iter()
+            .filter(|snake| snake.id != self.me)
+            .map(|snake| candidate_moves(self.game, board, snake))
+            .collect();
+
+        joint_moves(&moves)
+    }
+}
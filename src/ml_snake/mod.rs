@@ -1,2 +1,11 @@
+pub mod agent;
+pub mod baselines;
 pub mod logic;
-pub mod snake;
\ No newline at end of file
+pub mod mcts;
+pub mod minimax;
+pub mod multiplayer;
+pub mod safety;
+pub mod search;
+pub mod snake;
+pub mod space;
+pub mod territory;
\ No newline at end of file
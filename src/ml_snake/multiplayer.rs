@@ -0,0 +1,313 @@
+use std::time::Instant;
+
+use crate::{
+    constants::WIN_SCORE,
+    ml_snake::{
+        agent::Agent,
+        minimax::{paranoid_search, SearchResult},
+        search::{candidate_moves, search_or_fallback, Evaluator, SearchLimits},
+    },
+    simulation::{
+        make_move::{make_move, unmake_move},
+        rules::{Direction, Ruleset},
+    },
+    Battlesnake, Board, Game,
+};
+
+/// How a multi-player search decides what each turn is worth
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Backup {
+    /// Every opponent plays together against us. Cautious, and searches deeper since
+    /// alpha-beta can prune it
+    #[default]
+    Paranoid,
+    /// Every snake plays for its own score, picking in turn after the snakes before it.
+    /// Closer to how opponents really play, but nothing can be pruned
+    MaxN,
+}
+
+impl Backup {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backup::Paranoid => "paranoid",
+            Backup::MaxN => "max_n",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "paranoid" => Some(Backup::Paranoid),
+            "max_n" => Some(Backup::MaxN),
+            _ => None,
+        }
+    }
+}
+
+/// Looks ahead in games with any number of snakes by iterative deepening through the simulator's
+/// rules, backing scores up by `backup`. Falls back to `SpaceAgent` when there's nobody to play
+/// against or no time to search
+pub struct MultiplayerAgent<E: Evaluator> {
+    pub evaluator: E,
+    pub backup: Backup,
+    pub limits: SearchLimits,
+}
+
+impl<E: Evaluator> MultiplayerAgent<E> {
+    pub fn new(evaluator: E, backup: Backup) -> Self {
+        Self {
+            evaluator,
+            backup,
+            limits: SearchLimits::default(),
+        }
+    }
+
+    /// Searches one turn deeper at a time until `limits` run out or our outcome is decided. `None`
+    /// without opponents, or if not even one turn could be searched in time
+    pub fn search(&mut self, game: &Game, board: &Board, me: &Battlesnake) -> Option<SearchResult> {
+        if board.snakes.len() < 2 {
+            return None;
+        }
+
+        let deadline = self.limits.deadline(game);
+
+        match self.backup {
+            Backup::Paranoid => paranoid_search(
+                game,
+                board,
+                me,
+                &mut self.evaluator,
+                self.limits.max_depth,
+                deadline,
+            ),
+            Backup::MaxN => max_n_search(
+                game,
+                board,
+                me,
+                &mut self.evaluator,
+                self.limits.max_depth,
+                deadline,
+            ),
+        }
+    }
+}
+
+impl<E: Evaluator> Agent for MultiplayerAgent<E> {
+    fn get_move(
+        &mut self,
+        game: &Game,
+        turn: &i32,
+        board: &Board,
+        me: &Battlesnake,
+    ) -> Option<Direction> {
+        let searched_move = self.search(game, board, me).map(|result| result.best_move);
+
+        search_or_fallback(searched_move, game, turn, board, me)
+    }
+}
+
+fn max_n_search<E: Evaluator>(
+    game: &Game,
+    board: &Board,
+    me: &Battlesnake,
+    evaluator: &mut E,
+    max_depth: u32,
+    deadline: Instant,
+) -> Option<SearchResult> {
+    let mut search = MaxN {
+        game,
+        ruleset: Ruleset::from_game(game),
+        me: &me.id,
+        ids: board.snakes.iter().map(|snake| snake.id.clone()).collect(),
+        eliminated_at: vec![None; board.snakes.len()],
+        evaluator,
+        deadline,
+    };
+
+    let Some(my_index) = search.index_of(&me.id) else {
+        panic!("no snake {}", me.id);
+    };
+
+    let mut board = board.clone();
+    let mut result = None;
+
+    for depth in 1..=max_depth {
+        let Some((best_move, scores)) = search.turn(&mut board, depth, 0) else {
+            break;
+        };
+
+        let Some(best_move) = best_move else {
+            break;
+        };
+
+        let score = scores[my_index];
+
+        result = Some(SearchResult {
+            best_move,
+            score,
+            depth,
+        });
+
+        if score.abs() >= WIN_SCORE / 2. {
+            break;
+        }
+    }
+
+    result
+}
+
+/// The state shared by every node of one max-n search
+struct MaxN<'a, E: Evaluator> {
+    game: &'a Game,
+    ruleset: Ruleset,
+    me: &'a str,
+    /// Every snake on the board when the search began. Scores are listed in this order
+    ids: Vec<String>,
+    /// The ply each snake was eliminated on along the line being searched, in the order of `ids`
+    eliminated_at: Vec<Option<u32>>,
+    evaluator: &'a mut E,
+    deadline: Instant,
+}
+
+impl<E: Evaluator> MaxN<'_, E> {
+    /// Every snake's score for the position, searched `depth` turns deep, `ply` turns from the
+    /// root, along with our move. `None` when out of time
+    fn turn(
+        &mut self,
+        board: &mut Board,
+        depth: u32,
+        ply: u32,
+    ) -> Option<(Option<Direction>, Vec<f32>)> {
+        // Once we're eliminated our score can't change, however the others play on
+
+        let is_eliminated = board.snakes.iter().all(|snake| snake.id != self.me);
+
+        if board.snakes.len() < 2 || depth == 0 || is_eliminated {
+            return Some((None, self.leaf_scores(board, ply)));
+        }
+
+        if Instant::now() >= self.deadline {
+            return None;
+        }
+
+        // We pick first, so the others pick knowing our move, then each other snake in board order
+
+        let mut order: Vec<usize> = (0..board.snakes.len()).collect();
+        order.sort_by_key(|i| board.snakes[*i].id != self.me);
+
+        let candidates: Vec<Vec<Direction>> = order
+            .iter()
+            .map(|i| candidate_moves(self.game, board, &board.snakes[*i]))
+            .collect();
+
+        let mut moves = vec![Direction::Up; board.snakes.len()];
+
+        self.pick(board, &order, &candidates, 0, &mut moves, depth, ply)
+    }
+
+    /// The best move for the `picker`th snake in `order`, given the moves of those before it,
+    /// along with the scores it leads to. The last picker plays out the turn
+    #[allow(clippy::too_many_arguments)]
+    fn pick(
+        &mut self,
+        board: &mut Board,
+        order: &[usize],
+        candidates: &[Vec<Direction>],
+        picker: usize,
+        moves: &mut Vec<Direction>,
+        depth: u32,
+        ply: u32,
+    ) -> Option<(Option<Direction>, Vec<f32>)> {
+        let Some(board_index) = order.get(picker).copied() else {
+            let unmake = make_move(board, &self.ruleset, moves);
+            let eliminated = self.mark_eliminated(board, ply + 1);
+            let result = self.turn(board, depth - 1, ply + 1);
+
+            for score_index in eliminated {
+                self.eliminated_at[score_index] = None;
+            }
+
+            unmake_move(board, unmake);
+
+            return result;
+        };
+
+        let Some(score_index) = self.index_of(&board.snakes[board_index].id) else {
+            panic!("no snake {}", board.snakes[board_index].id);
+        };
+
+        let mut best: Option<(Direction, Vec<f32>)> = None;
+
+        for direction in &candidates[picker] {
+            moves[board_index] = *direction;
+
+            let (_, scores) = self.pick(board, order, candidates, picker + 1, moves, depth, ply)?;
+
+            if best
+                .as_ref()
+                .is_none_or(|(_, best_scores)| scores[score_index] > best_scores[score_index])
+            {
+                best = Some((*direction, scores));
+            }
+        }
+
+        let Some((direction, scores)) = best else {
+            panic!("no candidate moves");
+        };
+
+        // Only our pick is reported, which comes first
+
+        let our_move = (board.snakes[board_index].id == self.me).then_some(direction);
+
+        Some((our_move, scores))
+    }
+
+    /// Records the snakes missing from `board` that weren't already eliminated as eliminated on
+    /// `ply`, returning their score indexes
+    fn mark_eliminated(&mut self, board: &Board, ply: u32) -> Vec<usize> {
+        let mut eliminated = Vec::new();
+
+        for (score_index, id) in self.ids.iter().enumerate() {
+            if self.eliminated_at[score_index].is_none()
+                && board.snakes.iter().all(|snake| snake.id != *id)
+            {
+                self.eliminated_at[score_index] = Some(ply);
+                eliminated.push(score_index);
+            }
+        }
+
+        eliminated
+    }
+
+    /// Scores for a position the search stops at: a loss for every eliminated snake, sooner
+    /// being worse, a win for the last one standing, and otherwise the evaluator's opinion.
+    /// Snakes eliminated together on the last turn, leaving nobody, draw as in `paranoid_search`
+    fn leaf_scores(&mut self, board: &Board, ply: u32) -> Vec<f32> {
+        let mut scores: Vec<f32> = self
+            .eliminated_at
+            .iter()
+            .map(|eliminated_at| match *eliminated_at {
+                Some(eliminated_ply) if board.snakes.is_empty() && eliminated_ply == ply => 0.,
+                Some(eliminated_ply) => -WIN_SCORE + eliminated_ply as f32,
+                None => 0.,
+            })
+            .collect();
+
+        for snake in &board.snakes {
+            let Some(score_index) = self.index_of(&snake.id) else {
+                continue;
+            };
+
+            scores[score_index] = if board.snakes.len() == 1 {
+                WIN_SCORE - ply as f32
+            } else {
+                self.evaluator.evaluate(self.game, board, snake)
+            };
+        }
+
+        scores
+    }
+
+    fn index_of(&self, id: &str) -> Option<usize> {
+        self.ids.iter().position(|other| other == id)
+    }
+}
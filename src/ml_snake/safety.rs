@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+
+use crate::{
+    simulation::rules::{Direction, GameMode, Ruleset},
+    Battlesnake, Board, Coord, Game,
+};
+
+/// How safe a move is, from worst to best
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MoveSafety {
+    /// Off the board, or into a body that will still be there after the move
+    Illegal,
+    /// Into a cell an opponent at least as long could also move into, losing the head-on
+    RiskyHeadToHead,
+    Safe,
+}
+
+/// The safety of each move available to a snake this turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveMask {
+    moves: [(Direction, MoveSafety); 4],
+}
+
+impl MoveMask {
+    pub fn new(game: &Game, board: &Board, me: &Battlesnake) -> Self {
+        let ruleset = Ruleset::from_game(game);
+        let geometry = board
+            .geometry()
+            .with_wrapping(ruleset.mode == GameMode::Wrapped);
+
+        let blocked = blocked_coords(&ruleset, board, me);
+
+        // Cells opponents at least as long as us could move their heads into
+
+        let mut contested: HashSet<Coord> = HashSet::new();
+
+        for snake in &board.snakes {
+            if snake.id == me.id || snake.length < me.length {
+                continue;
+            }
+
+            contested.extend(geometry.neighbors(snake.head).map(|(_, coord)| coord));
+        }
+
+        let moves = Direction::ALL.map(|direction| {
+            let safety = match geometry.neighbor(me.head, direction) {
+                None => MoveSafety::Illegal,
+                Some(coord) if blocked.contains(&coord) => MoveSafety::Illegal,
+                Some(coord) if contested.contains(&coord) => MoveSafety::RiskyHeadToHead,
+                Some(_) => MoveSafety::Safe,
+            };
+
+            (direction, safety)
+        });
+
+        Self { moves }
+    }
+
+    pub fn safety(&self, direction: Direction) -> MoveSafety {
+        self.moves
+            .iter()
+            .find(|(other, _)| *other == direction)
+            .map_or(MoveSafety::Illegal, |(_, safety)| *safety)
+    }
+
+    /// The safety of the safest move available
+    pub fn best_safety(&self) -> MoveSafety {
+        self.moves
+            .iter()
+            .map(|(_, safety)| *safety)
+            .max()
+            .unwrap_or(MoveSafety::Illegal)
+    }
+
+    /// The moves as safe as the safest available. When every move is illegal, all of them are
+    /// allowed so there is still something to choose
+    pub fn allowed_moves(&self) -> Vec<Direction> {
+        let best_safety = self.best_safety();
+
+        self.moves
+            .iter()
+            .filter(|(_, safety)| *safety == best_safety)
+            .map(|(direction, _)| *direction)
+            .collect()
+    }
+
+    /// The highest scoring of the allowed moves. Equal scores go to the move listed first
+    pub fn choose(&self, scores: &[(Direction, f32)]) -> Option<Direction> {
+        let allowed_moves = self.allowed_moves();
+
+        scores
+            .iter()
+            .filter(|(direction, _)| allowed_moves.contains(direction))
+            .min_by(|(_, a), (_, b)| b.total_cmp(a))
+            .map(|(direction, _)| *direction)
+    }
+}
+
+/// The cells `me` can't move into this turn, whatever the other snakes do. Every body part but the
+/// tail stays put, and a tail stacked from eating keeps its cell anyway since the part before it
+/// is on the same cell. Squadmates that can be passed through don't block
+pub fn blocked_coords(ruleset: &Ruleset, board: &Board, me: &Battlesnake) -> HashSet<Coord> {
+    let mut blocked: HashSet<Coord> = HashSet::new();
+
+    for snake in &board.snakes {
+        let Some((_, body)) = snake.body.split_last() else {
+            continue;
+        };
+
+        if ruleset.is_passable(&me.squad, &snake.squad) && snake.id != me.id {
+            continue;
+        }
+
+        blocked.extend(body);
+    }
+
+    blocked
+}
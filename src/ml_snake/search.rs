@@ -0,0 +1,198 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    constants::{DEFAULT_MAX_SEARCH_DEPTH, SEARCH_LATENCY_MARGIN_MS},
+    ml_snake::{
+        agent::Agent,
+        logic::policy_value,
+        safety::{MoveMask, MoveSafety},
+        space::{SpaceAgent, SpaceAnalysis},
+        territory::Territory,
+    },
+    neural_network::NeuralNetwork,
+    simulation::{constants::SNAKE_MAX_HEALTH, rules::Direction},
+    Battlesnake, Board, Game,
+};
+
+/// Scores a board for one of the snakes still on it, higher being better for that snake.
+/// Searching agents call it on the positions they stop looking ahead from
+pub trait Evaluator {
+    fn evaluate(&mut self, game: &Game, board: &Board, me: &Battlesnake) -> f32;
+}
+
+/// A hand-written evaluation: how much longer `me` is than the longest opponent, how much of the
+/// board it can reach, how much of it it gets to first, and its health, each scaled by its weight
+#[derive(Debug, Clone, Copy)]
+pub struct HeuristicEvaluator {
+    /// Per body part longer than the longest opponent
+    pub length_weight: f32,
+    /// For reaching the whole board, as the largest area any move reaches
+    pub space_weight: f32,
+    /// For owning the whole board, as the share of cells it reaches before any opponent
+    pub territory_weight: f32,
+    /// For full health
+    pub health_weight: f32,
+}
+
+impl Default for HeuristicEvaluator {
+    fn default() -> Self {
+        Self {
+            length_weight: 1.,
+            space_weight: 10.,
+            territory_weight: 10.,
+            health_weight: 1.,
+        }
+    }
+}
+
+impl Evaluator for HeuristicEvaluator {
+    fn evaluate(&mut self, game: &Game, board: &Board, me: &Battlesnake) -> f32 {
+        let longest_opponent = board
+            .snakes
+            .iter()
+            .filter(|snake| snake.id != me.id)
+            .map(|snake| snake.length)
+            .max()
+            .unwrap_or(0);
+
+        let area = SpaceAnalysis::new(game, board, me)
+            .moves
+            .iter()
+            .map(|move_space| move_space.area)
+            .max()
+            .unwrap_or(0);
+
+        let territory = Territory::new(game, board).share(&me.id);
+
+        self.length_weight * (me.length - longest_opponent) as f32
+            + self.space_weight * area as f32 / board.geometry().area() as f32
+            + self.territory_weight * territory
+            + self.health_weight * me.health as f32 / SNAKE_MAX_HEALTH as f32
+    }
+}
+
+/// Move scores for `me`, higher being more promising, along with how good the board is for it.
+/// Tree search takes the scores as priors and the value as the worth of new positions
+pub trait PolicyValue {
+    fn policy_value(
+        &mut self,
+        game: &Game,
+        board: &Board,
+        me: &Battlesnake,
+    ) -> (Vec<(Direction, f32)>, f32);
+}
+
+impl PolicyValue for NeuralNetwork {
+    fn policy_value(
+        &mut self,
+        game: &Game,
+        board: &Board,
+        me: &Battlesnake,
+    ) -> (Vec<(Direction, f32)>, f32) {
+        policy_value(game, board, me, self)
+    }
+}
+
+/// Scores a board by the network's value output
+pub struct NeuralNetworkEvaluator {
+    pub network: NeuralNetwork,
+}
+
+impl NeuralNetworkEvaluator {
+    pub fn new(network: NeuralNetwork) -> Self {
+        Self { network }
+    }
+}
+
+impl Evaluator for NeuralNetworkEvaluator {
+    fn evaluate(&mut self, game: &Game, board: &Board, me: &Battlesnake) -> f32 {
+        let (_, value) = policy_value(game, board, me, &mut self.network);
+
+        value
+    }
+}
+
+/// How long and how deep a search that deepens one turn at a time may go
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchLimits {
+    /// How much of `Game.timeout` to leave unused
+    pub latency_margin: Duration,
+    /// The deepest search to try, in turns
+    pub max_depth: u32,
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        Self {
+            latency_margin: Duration::from_millis(SEARCH_LATENCY_MARGIN_MS),
+            max_depth: DEFAULT_MAX_SEARCH_DEPTH,
+        }
+    }
+}
+
+impl SearchLimits {
+    /// When a search starting now has to answer by
+    pub fn deadline(&self, game: &Game) -> Instant {
+        deadline(game, self.latency_margin)
+    }
+}
+
+/// How long a search can take to answer, leaving `latency_margin` of `Game.timeout` for the round trip
+pub fn time_budget(game: &Game, latency_margin: Duration) -> Duration {
+    Duration::from_millis(game.timeout as u64).saturating_sub(latency_margin)
+}
+
+/// When a search starting now has to answer by, leaving `latency_margin` of `Game.timeout` unused
+pub fn deadline(game: &Game, latency_margin: Duration) -> Instant {
+    Instant::now() + time_budget(game, latency_margin)
+}
+
+/// The move a search settled on, or `SpaceAgent`'s when it didn't settle on any, such as with
+/// nobody to play against or no time to search
+pub fn search_or_fallback(
+    searched_move: Option<Direction>,
+    game: &Game,
+    turn: &i32,
+    board: &Board,
+    me: &Battlesnake,
+) -> Option<Direction> {
+    searched_move.or_else(|| SpaceAgent.get_move(game, turn, board, me))
+}
+
+/// The moves worth searching for `snake`: all but the illegal ones. A snake with no legal move
+/// still gets one to lose with
+pub fn candidate_moves(game: &Game, board: &Board, snake: &Battlesnake) -> Vec<Direction> {
+    let move_mask = MoveMask::new(game, board, snake);
+
+    let moves: Vec<Direction> = Direction::ALL
+        .into_iter()
+        .filter(|direction| move_mask.safety(*direction) != MoveSafety::Illegal)
+        .collect();
+
+    if moves.is_empty() {
+        return vec![Direction::Up];
+    }
+
+    moves
+}
+
+/// Every way of picking one move from each list, keeping the lists' order
+pub fn joint_moves(moves: &[Vec<Direction>]) -> Vec<Vec<Direction>> {
+    let mut joint_moves: Vec<Vec<Direction>> = vec![Vec::new()];
+
+    for snake_moves in moves {
+        joint_moves = joint_moves
+            .into_iter()
+            .flat_map(|joint_move| {
+                snake_moves.iter().map(move |direction| {
+                    let mut joint_move = joint_move.clone();
+                    joint_move.push(*direction);
+
+                    joint_move
+                })
+            })
+            .collect();
+    }
+
+    joint_moves
+}
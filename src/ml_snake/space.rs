@@ -0,0 +1,156 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::{
+    geometry::Geometry,
+    ml_snake::{
+        agent::Agent,
+        safety::{blocked_coords, MoveMask},
+    },
+    simulation::rules::{Direction, GameMode, Ruleset},
+    Battlesnake, Board, Coord, Game,
+};
+
+/// How much room a move leaves to move around in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveSpace {
+    pub direction: Direction,
+    /// The number of cells reachable after the move, counting the one moved into.
+    /// Moves off the board or into a body have none
+    pub area: usize,
+    /// Whether our tail can be reached, so we can follow it however little room there is
+    pub reaches_tail: bool,
+    /// The area as a multiple of our length. Below 1 there is no room for our whole body
+    pub area_per_length: f32,
+}
+
+impl MoveSpace {
+    /// Whether the move leaves too little room to fit in, with no way to our tail
+    pub fn is_trapped(&self) -> bool {
+        !self.reaches_tail && self.area_per_length < 1.
+    }
+}
+
+/// The reachable space after each move available to a snake this turn, in `Direction::ALL` order
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpaceAnalysis {
+    pub moves: [MoveSpace; 4],
+}
+
+impl SpaceAnalysis {
+    pub fn new(game: &Game, board: &Board, me: &Battlesnake) -> Self {
+        let ruleset = Ruleset::from_game(game);
+        let geometry = board
+            .geometry()
+            .with_wrapping(ruleset.mode == GameMode::Wrapped);
+        let blocked = blocked_coords(&ruleset, board, me);
+        let tail = me.body.last().copied().unwrap_or(me.head);
+
+        let moves = Direction::ALL.map(|direction| {
+            let reachable = geometry
+                .neighbor(me.head, direction)
+                .map(|coord| reachable_coords(&geometry, &blocked, coord))
+                .unwrap_or_default();
+
+            // A tail stacked from eating is blocked for a turn, so being next to it is enough
+
+            let reaches_tail = reachable.contains(&tail)
+                || geometry
+                    .neighbors(tail)
+                    .any(|(_, coord)| reachable.contains(&coord));
+
+            MoveSpace {
+                direction,
+                area: reachable.len(),
+                reaches_tail,
+                area_per_length: reachable.len() as f32 / me.length.max(1) as f32,
+            }
+        });
+
+        Self { moves }
+    }
+
+    pub fn get(&self, direction: Direction) -> &MoveSpace {
+        let Some(move_space) = self
+            .moves
+            .iter()
+            .find(|move_space| move_space.direction == direction)
+        else {
+            panic!("no space for direction {:?}", direction);
+        };
+
+        move_space
+    }
+}
+
+/// Every cell reachable from `start` without crossing `blocked`, including `start`.
+/// Nothing is reachable from a blocked cell
+pub fn reachable_coords(
+    geometry: &Geometry,
+    blocked: &HashSet<Coord>,
+    start: Coord,
+) -> HashSet<Coord> {
+    if blocked.contains(&start) {
+        return HashSet::new();
+    }
+
+    let mut visited: HashSet<Coord> = HashSet::from([start]);
+    let mut frontier = VecDeque::from([start]);
+
+    while let Some(coord) = frontier.pop_front() {
+        for (_, neighbor) in geometry.neighbors(coord) {
+            if blocked.contains(&neighbor) || !visited.insert(neighbor) {
+                continue;
+            }
+
+            frontier.push_back(neighbor);
+        }
+    }
+
+    visited
+}
+
+/// Heads for the closest food through moves that don't trap it. When every move traps it,
+/// takes the one with the most room
+pub struct SpaceAgent;
+
+impl Agent for SpaceAgent {
+    fn get_move(
+        &mut self,
+        game: &Game,
+        _turn: &i32,
+        board: &Board,
+        me: &Battlesnake,
+    ) -> Option<Direction> {
+        let geometry = board
+            .geometry()
+            .with_wrapping(Ruleset::from_game(game).mode == GameMode::Wrapped);
+        let space_analysis = SpaceAnalysis::new(game, board, me);
+
+        // Moves that don't trap us score above the board's area, and those that do at most that
+
+        let scores: Vec<(Direction, f32)> = geometry
+            .neighbors(me.head)
+            .map(|(direction, coord)| {
+                let move_space = space_analysis.get(direction);
+
+                if move_space.is_trapped() {
+                    return (direction, move_space.area as f32);
+                }
+
+                let food_distance = board
+                    .food
+                    .iter()
+                    .map(|food| geometry.distance(coord, *food))
+                    .min()
+                    .unwrap_or(0);
+
+                (
+                    direction,
+                    (2 * geometry.area()) as f32 - food_distance as f32,
+                )
+            })
+            .collect();
+
+        MoveMask::new(game, board, me).choose(&scores)
+    }
+}
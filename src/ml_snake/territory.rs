@@ -0,0 +1,148 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    geometry::Geometry,
+    simulation::rules::{GameMode, Ruleset},
+    Board, Coord, Game,
+};
+
+/// The board split among the snakes by who can reach each cell first, a Voronoi partition.
+/// Every snake spreads out from its head a cell per turn at once, around every body part but the
+/// tails. A cell reached by several snakes on the same turn goes to the longest of them, since it
+/// would win the head-on there, and to nobody when the longest are tied
+#[derive(Debug, Clone, PartialEq)]
+pub struct Territory {
+    geometry: Geometry,
+    /// The owner of each cell, in packed order, as an index into `ids`
+    owners: Vec<Option<usize>>,
+    /// The snakes in the same order as `board.snakes`
+    ids: Vec<String>,
+    /// How many cells each snake owns, in the same order as `ids`
+    areas: Vec<usize>,
+}
+
+impl Territory {
+    pub fn new(game: &Game, board: &Board) -> Self {
+        let geometry = board
+            .geometry()
+            .with_wrapping(Ruleset::from_game(game).mode == GameMode::Wrapped);
+
+        let mut blocked: HashSet<Coord> = HashSet::new();
+
+        for snake in &board.snakes {
+            if let Some((_, body)) = snake.body.split_last() {
+                blocked.extend(body);
+            }
+        }
+
+        let mut owners: Vec<Option<usize>> = vec![None; geometry.area()];
+        let mut reached = vec![false; geometry.area()];
+        let mut frontiers: Vec<Vec<Coord>> = Vec::new();
+
+        for (i, snake) in board.snakes.iter().enumerate() {
+            let index = geometry.pack(snake.head);
+
+            owners[index] = Some(i);
+            reached[index] = true;
+            frontiers.push(vec![snake.head]);
+        }
+
+        while frontiers.iter().any(|frontier| !frontier.is_empty()) {
+            // Every cell reached this turn, with the snakes reaching it
+
+            let mut claims: HashMap<Coord, Vec<usize>> = HashMap::new();
+
+            for (i, frontier) in frontiers.iter().enumerate() {
+                for coord in frontier {
+                    for (_, neighbor) in geometry.neighbors(*coord) {
+                        if blocked.contains(&neighbor) || reached[geometry.pack(neighbor)] {
+                            continue;
+                        }
+
+                        let claimants = claims.entry(neighbor).or_default();
+
+                        if !claimants.contains(&i) {
+                            claimants.push(i);
+                        }
+                    }
+                }
+            }
+
+            for frontier in &mut frontiers {
+                frontier.clear();
+            }
+
+            for (coord, claimants) in claims {
+                let index = geometry.pack(coord);
+                reached[index] = true;
+
+                let longest = claimants
+                    .iter()
+                    .map(|i| board.snakes[*i].length)
+                    .max()
+                    .unwrap_or(0);
+
+                let [owner] = claimants
+                    .iter()
+                    .filter(|i| board.snakes[**i].length == longest)
+                    .collect::<Vec<_>>()[..]
+                else {
+                    continue;
+                };
+
+                owners[index] = Some(*owner);
+                frontiers[*owner].push(coord);
+            }
+        }
+
+        let mut areas = vec![0; board.snakes.len()];
+
+        for owner in owners.iter().flatten() {
+            areas[*owner] += 1;
+        }
+
+        Self {
+            geometry,
+            owners,
+            ids: board.snakes.iter().map(|snake| snake.id.clone()).collect(),
+            areas,
+        }
+    }
+
+    /// The id of the snake that owns `coord`, if any
+    pub fn owner(&self, coord: Coord) -> Option<&str> {
+        let owner = self
+            .owners
+            .get(self.geometry.pack(coord))
+            .copied()
+            .flatten()?;
+
+        Some(&self.ids[owner])
+    }
+
+    /// The number of cells the snake with `id` owns
+    pub fn area(&self, id: &str) -> usize {
+        self.ids
+            .iter()
+            .position(|other| other == id)
+            .map_or(0, |i| self.areas[i])
+    }
+
+    /// The share of the board the snake with `id` owns, from 0 to 1
+    pub fn share(&self, id: &str) -> f32 {
+        self.area(id) as f32 / self.geometry.area() as f32
+    }
+
+    /// One value per cell in packed order: 1 for cells the snake with `id` owns, -1 for cells
+    /// another snake owns and 0 for cells nobody does
+    pub fn plane(&self, id: &str) -> Vec<f32> {
+        self.owners
+            .iter()
+            .map(|owner| match owner {
+                Some(owner) if self.ids[*owner] == id => 1.,
+                Some(_) => -1.,
+                None => 0.,
+            })
+            .collect()
+    }
+}
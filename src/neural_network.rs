@@ -1,6 +1,5 @@
 use std::collections::HashMap;
 use std::fs;
-use std::sync::Mutex;
 
 use rand::Rng;
 extern crate rand;
@@ -12,14 +11,20 @@ pub struct NeuralNetworkManager {
 
 impl NeuralNetworkManager {
     pub fn new() -> Self {
-        return Self {
+        Self {
             id_index: 0,
             networks: HashMap::new(),
-        };
+        }
     }
     pub fn new_id(&mut self) -> String {
         self.id_index += 1;
-        return (&self.id_index).to_string();
+        self.id_index.to_string()
+    }
+}
+
+impl Default for NeuralNetworkManager {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -48,11 +53,11 @@ pub struct Input {
 
 impl Input {
     pub fn new(name: String, values: Vec<f32>, weight_ids: Vec<String>) -> Self {
-        return Input {
+        Input {
             name,
             values,
             weight_ids,
-        };
+        }
     }
 }
 
@@ -62,7 +67,7 @@ pub struct Output {
 
 impl Output {
     pub fn new(name: String) -> Self {
-        return Output { name };
+        Output { name }
     }
 }
 
@@ -102,16 +107,16 @@ impl NeuralNetwork {
     //     }
 
     pub fn new(neural_network_manager: &mut NeuralNetworkManager) -> Self {
-        return NeuralNetwork {
+        NeuralNetwork {
             id: neural_network_manager.new_id(),
             input_weight_layers: vec![],
             weights_by_id: HashMap::new(),
             weight_layers: vec![],
             activation_layers: vec![],
-        };
+        }
     }
 
-    pub fn build(&mut self, inputs: &Vec<Input>, output_count: usize) {
+    pub fn build(&mut self, inputs: &[Input], output_count: usize) {
         #[cfg(feature = "debug")]
         println!("Build");
 
@@ -194,10 +199,7 @@ impl NeuralNetwork {
         println!("{:?}", self.activation_layers);
     }
 
-    /**
-     *
-     */
-    pub fn forward_propagate(&mut self, inputs: &Vec<Input>) {
+    pub fn forward_propagate(&mut self, inputs: &[Input]) {
         #[cfg(feature = "debug")]
         println!("Foward prop");
 
@@ -226,6 +228,8 @@ impl NeuralNetwork {
 
         //
 
+        let output_layer_i = self.activation_layers.len() - 1;
+
         let mut layer_i = 1;
         while layer_i < self.activation_layers.len() {
             activation_i = 0;
@@ -233,9 +237,7 @@ impl NeuralNetwork {
                 self.activation_layers[layer_i][activation_i] = 0.;
 
                 let mut previous_layer_activation_i = 0;
-                while previous_layer_activation_i
-                    < self.activation_layers[(layer_i - 1) as usize].len()
-                {
+                while previous_layer_activation_i < self.activation_layers[layer_i - 1].len() {
                     self.activation_layers[layer_i][activation_i] += self.activation_layers
                         [layer_i - 1][previous_layer_activation_i]
                         * self.weight_layers[layer_i][activation_i][previous_layer_activation_i];
@@ -243,8 +245,15 @@ impl NeuralNetwork {
                     previous_layer_activation_i += 1;
                 }
 
-                self.activation_layers[layer_i][activation_i] =
-                    self.relu(self.activation_layers[layer_i][activation_i] + BIAS);
+                let activation = self.activation_layers[layer_i][activation_i] + BIAS;
+
+                // Outputs stay linear so they can go negative, such as a value for a losing board
+
+                self.activation_layers[layer_i][activation_i] = if layer_i == output_layer_i {
+                    activation
+                } else {
+                    self.relu(activation)
+                };
 
                 activation_i += 1;
             }
@@ -260,24 +269,22 @@ impl NeuralNetwork {
         if value > 0. {
             return value;
         }
-        return 0.;
+        0.
     }
 
     pub fn get_outputs(&self) -> &Vec<f32> {
         &self.activation_layers[self.activation_layers.len() - 1]
     }
 
-    pub fn back_propagate(&mut self, scored_outputs: bool) {}
+    pub fn back_propagate(&mut self, _scored_outputs: bool) {}
 
     /**
-     * Randomly increases or decreases weights
+     * Randomly increases or decreases weights, drawing only from `rng` so a seeded rng mutates identically
      */
-    pub fn mutate(&mut self) {
+    pub fn mutate(&mut self, rng: &mut impl Rng) {
         #[cfg(feature = "debug")]
         println!("Mutate");
 
-        let mut rng = rand::thread_rng();
-
         // Input layer
         /*
                let mut input_i = 0;
@@ -301,9 +308,15 @@ impl NeuralNetwork {
 
         // Mutate weights
 
-        for tuple in self.weights_by_id.clone() {
-            let new_weight = tuple.1 + rng.gen_range((-LEARNING_RATE)..=LEARNING_RATE);
-            self.weights_by_id.insert(tuple.0, new_weight);
+        // Hash map order changes between runs, so weights are visited in a fixed order
+
+        let mut weight_ids: Vec<String> = self.weights_by_id.keys().cloned().collect();
+        weight_ids.sort();
+
+        for weight_id in weight_ids {
+            let new_weight =
+                self.weights_by_id[&weight_id] + rng.gen_range((-LEARNING_RATE)..=LEARNING_RATE);
+            self.weights_by_id.insert(weight_id, new_weight);
         }
 
         // Construct new weight layers
@@ -315,7 +328,7 @@ impl NeuralNetwork {
                 let weight_id = self.input_weight_layers[input_i][value_i].to_string();
                 let present_weight = self.weights_by_id.get(&weight_id).unwrap();
 
-                self.weight_layers[0][input_i][value_i] = present_weight.clone();
+                self.weight_layers[0][input_i][value_i] = *present_weight;
 
                 value_i += 1;
             }
@@ -366,7 +379,11 @@ impl NeuralNetwork {
 
     pub fn update_visuals(&mut self) {}
 
-    pub fn clone(&self, neural_network_manager: &mut NeuralNetworkManager) -> NeuralNetwork {
+    /// A copy of the network under a new id from `neural_network_manager`
+    pub fn clone_with_id(
+        &self,
+        neural_network_manager: &mut NeuralNetworkManager,
+    ) -> NeuralNetwork {
         let new_neural_network = NeuralNetwork {
             id: neural_network_manager.new_id(),
             input_weight_layers: self.input_weight_layers.clone(),
@@ -376,6 +393,6 @@ impl NeuralNetwork {
         };
         /* new_neural_network.new(); */
 
-        return new_neural_network;
+        new_neural_network
     }
 }
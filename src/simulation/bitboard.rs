@@ -0,0 +1,846 @@
+use std::fmt;
+
+use rand::Rng;
+
+use crate::{geometry::Geometry, Battlesnake, Board, Coord};
+
+use super::{
+    constants::{MAX_BITBOARD_SIZE, MAX_BITBOARD_SNAKES, SNAKE_MAX_HEALTH, SNAKE_STARTING_LENGTH},
+    rules::{Direction, EliminatedCause, Elimination, GameMode, Ruleset},
+};
+
+const MAX_CELLS: usize = (MAX_BITBOARD_SIZE * MAX_BITBOARD_SIZE) as usize;
+const WORDS: usize = MAX_CELLS.div_ceil(64);
+
+/// Room for a snake filling the whole board, with its starting parts still stacked
+const BODY_CAPACITY: usize = MAX_CELLS + SNAKE_STARTING_LENGTH as usize;
+
+/// A set of cells, one bit per cell, indexed as `Geometry` packs them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BitBoard([u64; WORDS]);
+
+impl BitBoard {
+    pub fn contains(&self, index: usize) -> bool {
+        self.0[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.0[index / 64] &= !(1 << (index % 64));
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|word| *word == 0)
+    }
+
+    /// The cells in the set, lowest index first
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(word_i, word)| {
+            let mut word = *word;
+
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+
+                Some(word_i * 64 + bit)
+            })
+        })
+    }
+}
+
+/// A snake's body as cell indexes, head first. It is kept in a fixed ring, so moving and growing
+/// never allocate
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Body {
+    cells: [u16; BODY_CAPACITY],
+    start: usize,
+    len: usize,
+}
+
+impl Body {
+    fn new(cells: impl Iterator<Item = usize>) -> Self {
+        let mut body = Self {
+            cells: [0; BODY_CAPACITY],
+            start: 0,
+            len: 0,
+        };
+
+        body.set(cells);
+
+        body
+    }
+
+    /// Replaces the body with `cells`, head first
+    fn set(&mut self, cells: impl Iterator<Item = usize>) {
+        self.start = 0;
+        self.len = 0;
+
+        for index in cells {
+            self.push_back(index);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The `i`th body part from the head
+    pub fn get(&self, i: usize) -> usize {
+        self.cells[(self.start + i) % BODY_CAPACITY] as usize
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+
+    fn push_front(&mut self, index: usize) {
+        assert!(self.len < BODY_CAPACITY, "snake is too long for a bitboard");
+
+        self.start = (self.start + BODY_CAPACITY - 1) % BODY_CAPACITY;
+        self.cells[self.start] = index as u16;
+        self.len += 1;
+    }
+
+    fn push_back(&mut self, index: usize) {
+        assert!(self.len < BODY_CAPACITY, "snake is too long for a bitboard");
+
+        self.cells[(self.start + self.len) % BODY_CAPACITY] = index as u16;
+        self.len += 1;
+    }
+
+    fn pop_back(&mut self) {
+        self.len = self.len.saturating_sub(1);
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Cells in the order they were listed, kept in a fixed array so adding one never allocates
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct CellList {
+    cells: [u16; MAX_CELLS],
+    len: usize,
+}
+
+impl CellList {
+    fn new() -> Self {
+        Self {
+            cells: [0; MAX_CELLS],
+            len: 0,
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.cells[..self.len].iter().map(|index| *index as usize)
+    }
+
+    fn push(&mut self, index: usize) {
+        self.cells[self.len] = index as u16;
+        self.len += 1;
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Keeps the cells `f` returns true for, in the same order
+    fn retain(&mut self, mut f: impl FnMut(usize) -> bool) {
+        let mut kept = 0;
+
+        for i in 0..self.len {
+            let index = self.cells[i];
+
+            if f(index as usize) {
+                self.cells[kept] = index;
+                kept += 1;
+            }
+        }
+
+        self.len = kept;
+    }
+}
+
+impl fmt::Debug for CellList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// A snake on a `FastBoard`. Who it is lives on the board it was made from, found by `slot`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FastSnake {
+    /// The snake's index in the board this was made from
+    pub slot: usize,
+    /// The slot of the first snake in the same squad, so squadmates share it
+    pub squad: usize,
+    pub health: i32,
+    pub body: Body,
+    /// Where the head went if it left the board this turn, in which case `body` is the rest of
+    /// the snake. Such snakes are eliminated before the turn is over
+    pub off_board_head: Option<Coord>,
+}
+
+impl FastSnake {
+    pub fn head(&self) -> usize {
+        self.body.get(0)
+    }
+
+    pub fn length(&self) -> usize {
+        self.body.len() + usize::from(self.off_board_head.is_some())
+    }
+
+    fn grow(&mut self) {
+        self.health = SNAKE_MAX_HEALTH;
+
+        if !self.body.is_empty() {
+            self.body.push_back(self.body.get(self.body.len() - 1));
+        }
+    }
+}
+
+/// Why a snake left a `FastBoard`, as `EliminatedCause` but with other snakes given by slot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FastCause {
+    OutOfBounds,
+    SelfCollision,
+    BodyCollision(usize),
+    HeadToHead(usize),
+    Starvation,
+    Hazard,
+    SquadEliminated,
+}
+
+/// A snake as it was when eliminated from a `FastBoard`, with why and on which turn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FastElimination {
+    pub snake: FastSnake,
+    pub cause: FastCause,
+    pub turn: i32,
+}
+
+impl FastElimination {
+    /// The elimination the rules would have recorded. `board` is the one the `FastBoard` was made
+    /// from, which names the snakes
+    pub fn to_elimination(&self, board: &Board) -> Elimination {
+        let id = |slot: usize| board.snakes[slot].id.clone();
+
+        let cause = match self.cause {
+            FastCause::OutOfBounds => EliminatedCause::OutOfBounds,
+            FastCause::SelfCollision => EliminatedCause::SelfCollision,
+            FastCause::BodyCollision(slot) => EliminatedCause::BodyCollision(id(slot)),
+            FastCause::HeadToHead(slot) => EliminatedCause::HeadToHead(id(slot)),
+            FastCause::Starvation => EliminatedCause::Starvation,
+            FastCause::Hazard => EliminatedCause::Hazard,
+            FastCause::SquadEliminated => EliminatedCause::SquadEliminated,
+        };
+
+        let mut snake = board.snakes[self.snake.slot].clone();
+        write_snake(&board.geometry(), &self.snake, &mut snake);
+
+        Elimination {
+            snake,
+            cause,
+            turn: self.turn,
+        }
+    }
+}
+
+/// A compact board for running many games quickly, with food as a bitboard, hazards as a count
+/// per cell and snakes as cell indexes. Stepping it allocates nothing, and neither does loading
+/// a board into one that already holds as many snakes. Supports boards up to 25x25 with up to
+/// 64 snakes
+#[derive(Debug, Clone)]
+pub struct FastBoard {
+    pub geometry: Geometry,
+    pub food: BitBoard,
+    /// The food in the order the rules list it: as it was loaded, then as it spawned
+    food_order: CellList,
+    /// How many hazards are stacked on each cell
+    pub hazards: [u16; MAX_CELLS],
+    pub snakes: Vec<FastSnake>,
+}
+
+impl FastBoard {
+    /// Whether `board` is small enough, with few enough snakes, to become a `FastBoard`
+    pub fn fits(board: &Board) -> bool {
+        board.width <= MAX_BITBOARD_SIZE
+            && board.height <= MAX_BITBOARD_SIZE
+            && board.snakes.len() <= MAX_BITBOARD_SNAKES
+    }
+
+    pub fn from_board(board: &Board) -> Self {
+        assert!(
+            Self::fits(board),
+            "board is larger than {0}x{0} or has more than {1} snakes",
+            MAX_BITBOARD_SIZE,
+            MAX_BITBOARD_SNAKES
+        );
+
+        let mut fast_board = Self {
+            geometry: board.geometry(),
+            food: BitBoard::default(),
+            food_order: CellList::new(),
+            hazards: [0; MAX_CELLS],
+            snakes: Vec::with_capacity(board.snakes.len()),
+        };
+
+        fast_board.load(board);
+
+        fast_board
+    }
+
+    /// Makes this the same position as `from_board(board)` would, reusing its snakes' bodies so
+    /// that nothing is allocated unless `board` has more snakes than this ever held
+    pub fn load(&mut self, board: &Board) {
+        assert!(
+            Self::fits(board),
+            "board is larger than {0}x{0} or has more than {1} snakes",
+            MAX_BITBOARD_SIZE,
+            MAX_BITBOARD_SNAKES
+        );
+
+        let geometry = board.geometry();
+        self.geometry = geometry;
+
+        self.food = BitBoard::default();
+        self.food_order.clear();
+
+        for food in &board.food {
+            let index = geometry.pack(*food);
+
+            if !self.food.contains(index) {
+                self.food.insert(index);
+                self.food_order.push(index);
+            }
+        }
+
+        self.hazards = [0; MAX_CELLS];
+
+        for hazard in &board.hazards {
+            let index = geometry.pack(*hazard);
+            self.hazards[index] = self.hazards[index].saturating_add(1);
+        }
+
+        self.snakes.truncate(board.snakes.len());
+
+        for (slot, snake) in board.snakes.iter().enumerate() {
+            // Outside squad games every squad is empty, which is quicker to check than comparing
+
+            let squad = if snake.squad.is_empty() {
+                board.snakes.iter().position(|other| other.squad.is_empty())
+            } else {
+                board
+                    .snakes
+                    .iter()
+                    .position(|other| other.squad == snake.squad)
+            }
+            .unwrap_or(slot);
+            let body = snake.body.iter().map(|body_part| geometry.pack(*body_part));
+
+            match self.snakes.get_mut(slot) {
+                Some(fast_snake) => {
+                    fast_snake.slot = slot;
+                    fast_snake.squad = squad;
+                    fast_snake.health = snake.health;
+                    fast_snake.body.set(body);
+                    fast_snake.off_board_head = None;
+                }
+                None => self.snakes.push(FastSnake {
+                    slot,
+                    squad,
+                    health: snake.health,
+                    body: Body::new(body),
+                    off_board_head: None,
+                }),
+            }
+        }
+    }
+
+    /// The API board for this position. `board` is the one this was made from, which names the
+    /// snakes
+    pub fn to_board(&self, board: &Board) -> Board {
+        let mut board = board.clone();
+        self.update_board(&mut board);
+
+        board
+    }
+
+    /// Brings `board`, the one this was made from, to this position, reusing its allocations.
+    /// Eliminated snakes are removed. Food is listed as the rules list it, with what is still
+    /// there in its place and new food in the order it spawned. Hazards are listed column by
+    /// column, the order the rules list cells in, rather than in the row by row order `Geometry`
+    /// packs them
+    pub fn update_board(&self, board: &mut Board) {
+        // Snakes only ever leave, so the survivors are still in slot order
+
+        let mut snakes = self.snakes.iter().peekable();
+        let mut slot = 0;
+
+        board.snakes.retain_mut(|snake| {
+            let fast_snake = snakes.next_if(|fast_snake| fast_snake.slot == slot);
+            slot += 1;
+
+            let Some(fast_snake) = fast_snake else {
+                return false;
+            };
+
+            write_snake(&self.geometry, fast_snake, snake);
+
+            true
+        });
+
+        // Food set on `food` directly isn't in `food_order`, so it follows column by column
+
+        let mut unlisted_food = self.food;
+
+        board.food.clear();
+
+        for index in self.food_order.iter() {
+            if self.food.contains(index) {
+                unlisted_food.remove(index);
+                board.food.push(self.geometry.unpack(index));
+            }
+        }
+
+        if !unlisted_food.is_empty() {
+            board.food.extend(
+                self.column_order(unlisted_food)
+                    .map(|index| self.geometry.unpack(index)),
+            );
+        }
+
+        board.hazards.clear();
+
+        // Most games have none. Or-ing every count is quicker than searching for one
+
+        if self.hazards.iter().fold(0, |any, count| any | count) == 0 {
+            return;
+        }
+
+        for x in 0..self.geometry.width {
+            for y in 0..self.geometry.height {
+                let index = self.geometry.pack(Coord { x, y });
+
+                for _ in 0..self.hazards[index] {
+                    board.hazards.push(Coord { x, y });
+                }
+            }
+        }
+    }
+
+    /// The cell a move from `index` lands on, or `None` when it leaves a board that doesn't wrap
+    pub fn neighbor(&self, index: usize, direction: Direction, mode: GameMode) -> Option<usize> {
+        self.geometry
+            .with_wrapping(mode == GameMode::Wrapped)
+            .neighbor(self.geometry.unpack(index), direction)
+            .map(|coord| self.geometry.pack(coord))
+    }
+
+    /// Resolves one turn in place, exactly as `rules::step` would with the same rng, and adds the
+    /// snakes eliminated during it to `eliminated` in the order the rules record them.
+    /// Callers stepping many times can reuse one `eliminated` so nothing is allocated
+    pub fn step(
+        &mut self,
+        turn: i32,
+        ruleset: &Ruleset,
+        moves: &[Direction],
+        rng: &mut impl Rng,
+        eliminated: &mut Vec<FastElimination>,
+    ) {
+        assert_eq!(
+            moves.len(),
+            self.snakes.len(),
+            "expected one move per snake"
+        );
+
+        // Eliminations are recorded on the turn of the board they produce
+
+        let next_turn = turn + 1;
+        let first_eliminated = eliminated.len();
+
+        // Move. Snakes leaving the board keep the rest of their body until they are eliminated,
+        // and can't eat or be hurt meanwhile
+
+        let geometry = self
+            .geometry
+            .with_wrapping(ruleset.mode == GameMode::Wrapped);
+
+        for (snake, direction) in self.snakes.iter_mut().zip(moves) {
+            let head = direction.apply(self.geometry.unpack(snake.head()));
+
+            match geometry.on_board(head) {
+                Some(head) => snake.body.push_front(geometry.pack(head)),
+                None => snake.off_board_head = Some(head),
+            }
+
+            snake.body.pop_back();
+            snake.health -= 1;
+        }
+
+        // Damage hazards
+
+        let food = self.food;
+        let hazards = &self.hazards;
+
+        self.snakes.retain_mut(|snake| {
+            if snake.off_board_head.is_some() || food.contains(snake.head()) {
+                return true;
+            }
+
+            let hazards_count = hazards[snake.head()] as i32;
+
+            if hazards_count == 0 {
+                return true;
+            }
+
+            snake.health = (snake.health - ruleset.hazard_damage_per_turn * hazards_count).max(0);
+
+            if snake.health > 0 {
+                return true;
+            }
+
+            eliminated.push(FastElimination {
+                snake: *snake,
+                cause: FastCause::Hazard,
+                turn: next_turn,
+            });
+
+            false
+        });
+
+        // Feed
+
+        let mut eaten_food = BitBoard::default();
+
+        for snake in &mut self.snakes {
+            if snake.off_board_head.is_none() && self.food.contains(snake.head()) {
+                eaten_food.insert(snake.head());
+                snake.grow();
+            }
+        }
+
+        if !eaten_food.is_empty() {
+            for index in eaten_food.iter() {
+                self.food.remove(index);
+            }
+
+            self.food_order.retain(|index| !eaten_food.contains(index));
+        }
+
+        if ruleset.mode == GameMode::Constrictor {
+            self.food = BitBoard::default();
+            self.food_order.clear();
+        } else {
+            self.spawn_food(ruleset, rng);
+        }
+
+        // Eliminate starved snakes and those off the board, then find every collision before
+        // removing anyone
+
+        self.snakes.retain(|snake| {
+            let cause = if snake.health <= 0 {
+                FastCause::Starvation
+            } else if snake.off_board_head.is_some() {
+                FastCause::OutOfBounds
+            } else {
+                return true;
+            };
+
+            eliminated.push(FastElimination {
+                snake: *snake,
+                cause,
+                turn: next_turn,
+            });
+
+            false
+        });
+
+        let mut causes = [None; MAX_BITBOARD_SNAKES];
+
+        for (i, snake) in self.snakes.iter().enumerate() {
+            causes[i] = self.collision(ruleset, snake);
+        }
+
+        let mut causes = causes.into_iter();
+
+        self.snakes.retain(|snake| {
+            let Some(cause) = causes.next().flatten() else {
+                return true;
+            };
+
+            eliminated.push(FastElimination {
+                snake: *snake,
+                cause,
+                turn: next_turn,
+            });
+
+            false
+        });
+
+        match ruleset.mode {
+            GameMode::Royale => self.shrink_hazards(ruleset, next_turn, rng),
+            GameMode::Constrictor => {
+                for snake in &mut self.snakes {
+                    snake.grow();
+                }
+            }
+            GameMode::Squad => {
+                self.share_squad_attributes(ruleset, next_turn, eliminated, first_eliminated)
+            }
+            GameMode::Standard | GameMode::Wrapped => {}
+        }
+    }
+
+    /// Matches the collisions of `rules::eliminate_snakes`: with itself, then the first body in
+    /// board order it can't pass through, then the longest head it met if that is at least as long
+    fn collision(&self, ruleset: &Ruleset, snake: &FastSnake) -> Option<FastCause> {
+        let head = snake.head();
+
+        // Heads are excluded so that heads meeting are treated as head-on collisions
+
+        let is_body = |other: &FastSnake| other.body.iter().skip(1).any(|index| index == head);
+
+        if is_body(snake) {
+            return Some(FastCause::SelfCollision);
+        }
+
+        let is_squad_passable = ruleset.mode == GameMode::Squad && ruleset.allow_body_collisions;
+
+        if let Some(other) = self.snakes.iter().find(|other| {
+            other.slot != snake.slot
+                && !(is_squad_passable && other.squad == snake.squad)
+                && is_body(other)
+        }) {
+            return Some(FastCause::BodyCollision(other.slot));
+        }
+
+        // `max_by_key` keeps the last of equals, like the rules
+
+        let longest_other = self
+            .snakes
+            .iter()
+            .filter(|other| other.slot != snake.slot && other.head() == head)
+            .max_by_key(|other| other.length())?;
+
+        if longest_other.length() >= snake.length() {
+            return Some(FastCause::HeadToHead(longest_other.slot));
+        }
+
+        None
+    }
+
+    /// Matches `rules::share_squad_attributes`
+    fn share_squad_attributes(
+        &mut self,
+        ruleset: &Ruleset,
+        turn: i32,
+        eliminated: &mut Vec<FastElimination>,
+        first_eliminated: usize,
+    ) {
+        if ruleset.shared_elimination && eliminated.len() > first_eliminated {
+            let mut eliminated_squads = [false; MAX_BITBOARD_SNAKES];
+
+            for elimination in &eliminated[first_eliminated..] {
+                eliminated_squads[elimination.snake.squad] = true;
+            }
+
+            self.snakes.retain(|snake| {
+                if !eliminated_squads[snake.squad] {
+                    return true;
+                }
+
+                eliminated.push(FastElimination {
+                    snake: *snake,
+                    cause: FastCause::SquadEliminated,
+                    turn,
+                });
+
+                false
+            });
+        }
+
+        for i in 0..self.snakes.len() {
+            let squadmates = self
+                .snakes
+                .iter()
+                .filter(|other| other.squad == self.snakes[i].squad);
+
+            let (health, length) = squadmates.fold((0, 0), |(health, length), other| {
+                (health.max(other.health), length.max(other.length()))
+            });
+
+            let snake = &mut self.snakes[i];
+
+            if ruleset.shared_health {
+                snake.health = health;
+            }
+
+            if ruleset.shared_length {
+                while snake.length() < length {
+                    let tail = snake.body.get(snake.length() - 1);
+                    snake.body.push_back(tail);
+                }
+            }
+        }
+    }
+
+    /// Matches `rules::spawn_food`, drawing from the rng in the same way
+    fn spawn_food(&mut self, ruleset: &Ruleset, rng: &mut impl Rng) {
+        let food_count = self.food.len() as i32;
+
+        let count = if food_count < ruleset.minimum_food {
+            ruleset.minimum_food - food_count
+        } else if ruleset.food_spawn_chance > 0
+            && 100 - rng.gen_range(0..100) < ruleset.food_spawn_chance
+        {
+            1
+        } else {
+            0
+        };
+
+        for _ in 0..count {
+            let unoccupied = self.unoccupied_cells();
+            let unoccupied_count = unoccupied.len();
+
+            if unoccupied_count == 0 {
+                return;
+            }
+
+            // The same draw `SliceRandom::choose` makes over the rules' list of cells
+
+            let chosen = rng.gen_range(0..unoccupied_count as u32) as usize;
+            let index = self.column_order(unoccupied).nth(chosen).unwrap();
+
+            self.food.insert(index);
+            self.food_order.push(index);
+        }
+    }
+
+    /// The same cells as `rules::unoccupied_coords`. Snakes that just left the board still
+    /// block their body and the cells around their head
+    fn unoccupied_cells(&self) -> BitBoard {
+        let mut occupied = self.food;
+
+        for snake in &self.snakes {
+            for body_part in snake.body.iter() {
+                occupied.insert(body_part);
+            }
+
+            let head = snake
+                .off_board_head
+                .unwrap_or_else(|| self.geometry.unpack(snake.head()));
+
+            for direction in Direction::ALL {
+                let coord = direction.apply(head);
+
+                if self.geometry.contains(coord) {
+                    occupied.insert(self.geometry.pack(coord));
+                }
+            }
+        }
+
+        let mut unoccupied = BitBoard::default();
+
+        for index in 0..self.geometry.area() {
+            if !occupied.contains(index) && self.hazards[index] == 0 {
+                unoccupied.insert(index);
+            }
+        }
+
+        unoccupied
+    }
+
+    /// The cells of `cells` column by column, the order the rules list cells in
+    fn column_order(&self, cells: BitBoard) -> impl Iterator<Item = usize> + '_ {
+        (0..self.geometry.width).flat_map(move |x| {
+            (0..self.geometry.height)
+                .map(move |y| self.geometry.pack(Coord { x, y }))
+                .filter(move |index| cells.contains(*index))
+        })
+    }
+
+    /// Matches `rules::shrink_hazards`, drawing from the rng in the same way
+    fn shrink_hazards(&mut self, ruleset: &Ruleset, turn: i32, rng: &mut impl Rng) {
+        if ruleset.shrink_every_n_turns < 1 || turn % ruleset.shrink_every_n_turns != 0 {
+            return;
+        }
+
+        let mut min_x = self.geometry.width;
+        let mut max_x = -1;
+        let mut min_y = self.geometry.height;
+        let mut max_y = -1;
+
+        for x in 0..self.geometry.width {
+            for y in 0..self.geometry.height {
+                if self.hazards[self.geometry.pack(Coord { x, y })] > 0 {
+                    continue;
+                }
+
+                min_x = min_x.min(x);
+                max_x = max_x.max(x);
+                min_y = min_y.min(y);
+                max_y = max_y.max(y);
+            }
+        }
+
+        // The whole board is already hazardous
+
+        if max_x < 0 {
+            return;
+        }
+
+        match rng.gen_range(0..4) {
+            0 if min_x < max_x => min_x += 1,
+            1 if max_x > min_x => max_x -= 1,
+            2 if min_y < max_y => min_y += 1,
+            3 if max_y > min_y => max_y -= 1,
+            _ => {}
+        }
+
+        self.hazards = [0; MAX_CELLS];
+
+        for x in 0..self.geometry.width {
+            for y in 0..self.geometry.height {
+                if x < min_x || x > max_x || y < min_y || y > max_y {
+                    self.hazards[self.geometry.pack(Coord { x, y })] = 1;
+                }
+            }
+        }
+    }
+}
+
+/// Writes where a `FastSnake` is into the snake it was made from
+fn write_snake(geometry: &Geometry, fast_snake: &FastSnake, snake: &mut Battlesnake) {
+    snake.health = fast_snake.health;
+
+    snake.body.clear();
+    snake.body.extend(fast_snake.off_board_head);
+    snake
+        .body
+        .extend(fast_snake.body.iter().map(|index| geometry.unpack(index)));
+
+    if let Some(head) = snake.body.first() {
+        snake.head = *head;
+    }
+
+    snake.length = snake.body.len() as i32;
+}
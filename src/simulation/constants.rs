@@ -9,5 +9,47 @@ pub mod graphics {
     pub const BODY_RIGHT: &str = ">";
 }
 
-// The number of body parts the snake will have once it has fully moved on its original position
-pub const SNAKE_STARTING_LENGTH: i32 = 3;
\ No newline at end of file
+// The number of body parts a snake starts with, all stacked on its head
+pub const SNAKE_STARTING_LENGTH: i32 = 3;
+
+// Board sizes that use the official fixed spawn points rather than random ones
+pub const FIXED_SPAWN_BOARD_SIZES: [(i32, i32); 3] = [(7, 7), (11, 11), (19, 19)];
+
+// The turn a game is stopped and adjudicated on if it hasn't ended, so circling snakes can't stall training
+pub const DEFAULT_MAX_TURNS: i32 = 1000;
+
+// The widest and tallest board a bitboard can hold
+pub const MAX_BITBOARD_SIZE: i32 = 25;
+
+// The most snakes a bitboard can hold
+pub const MAX_BITBOARD_SNAKES: usize = 64;
+
+// The health a snake starts with and is restored to when it eats
+pub const SNAKE_MAX_HEALTH: i32 = 100;
+
+// Extra health lost each turn a snake's head spends in a hazard, unless the ruleset says otherwise
+pub const DEFAULT_HAZARD_DAMAGE_PER_TURN: i32 = 14;
+
+// The fewest food the board is topped up to every turn, unless the ruleset says otherwise
+pub const DEFAULT_MINIMUM_FOOD: i32 = 1;
+
+// The percent chance each turn of spawning an extra food when there is at least the minimum
+pub const DEFAULT_FOOD_SPAWN_CHANCE: i32 = 15;
+
+// How many squads the snakes of a Squad game are dealt into
+pub const SQUADS_COUNT: u32 = 2;
+
+// Whether squadmates can move through each other's bodies, unless the ruleset says otherwise
+pub const DEFAULT_ALLOW_BODY_COLLISIONS: bool = true;
+
+// Whether a squad is eliminated together when any of its snakes is, unless the ruleset says otherwise
+pub const DEFAULT_SHARED_ELIMINATION: bool = true;
+
+// Whether squadmates all take the health of the healthiest, unless the ruleset says otherwise
+pub const DEFAULT_SHARED_HEALTH: bool = true;
+
+// Whether squadmates all grow to the length of the longest, unless the ruleset says otherwise
+pub const DEFAULT_SHARED_LENGTH: bool = true;
+
+// How many turns pass between each Royale shrink, unless the ruleset says otherwise
+pub const DEFAULT_SHRINK_EVERY_N_TURNS: i32 = 25;
@@ -1,34 +1,65 @@
 use std::{
-    collections::{HashMap, HashSet},
-    usize,
+    collections::HashMap,
+    time::{Duration, Instant},
 };
 
 use colored::Colorize;
-use rand::{random, Rng};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 
-use crate::{
-    ml_snake::logic::{choose_move, get_move},
-    neural_network::NeuralNetwork,
-    utils::{get_direction, is_out_of_bounds, pack_coord, pack_xy, random_coord, unpack_coord},
-    Battlesnake, Board, Coord, Game, GameState,
-};
+use crate::{ml_snake::agent::Agent, Battlesnake, Board, Coord, Game, GameState};
 
-use super::constants::{graphics, SNAKE_STARTING_LENGTH};
+use super::{
+    bitboard::{FastBoard, FastElimination},
+    constants::{
+        graphics, DEFAULT_MAX_TURNS, FIXED_SPAWN_BOARD_SIZES, SNAKE_MAX_HEALTH,
+        SNAKE_STARTING_LENGTH, SQUADS_COUNT,
+    },
+    rules::{self, Direction, Elimination, Event, GameMode, Ruleset},
+};
 
+#[derive(Clone)]
 pub struct GameWrapper {
     pub turn: i32,
     pub game: Game,
     pub board: Board,
+    /// Snakes removed from the board, in the order they were eliminated
+    pub eliminations: Vec<Elimination>,
+    /// Drives every random choice in the game, so a seed replays it exactly
+    pub rng: StdRng,
+    /// The game is adjudicated once this many turns have been played
+    pub max_turns: i32,
+    pub adjudication: Adjudication,
+    /// Whether moves slower than `Game.timeout` are missed, as on the engine. Off by default, since
+    /// how long a move takes depends on the machine and seeded games should replay the same anywhere
+    pub enforce_timeout: bool,
+    /// Kept between turns so stepping a board that fits reuses its buffers
+    fast_board: Option<FastBoard>,
+    fast_eliminated: Vec<FastElimination>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum GameStepOutcome {
     Winner(String),
+    /// Every remaining snake belongs to the squad with this id
+    SquadWinner(String),
     Tie,
+    /// The game reached its turn limit and was adjudicated, naming the snake ranked first
+    /// or none for a draw
+    TurnLimit(Option<String>),
     None,
 }
 
-#[derive(Debug)]
+/// How a game that reaches its turn limit is decided
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Adjudication {
+    /// Nobody wins
+    #[default]
+    Draw,
+    /// The longest snake wins, then the healthiest. Snakes equal on both draw
+    Rank,
+}
+
+#[derive(Debug, Clone)]
 pub enum CoordType {
     Head,
     BodyUp,
@@ -40,280 +71,423 @@ pub enum CoordType {
     Empty,
 }
 
+/// How snakes are laid out at the start of a game
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SnakeSpawn {
+    /// The official engine's layout: fixed corner and edge spawns with food beside each snake on
+    /// 7x7, 11x11 and 19x19 boards, falling back to random spawns on other sizes
+    #[default]
+    Official,
+    /// Random spawns on any board, to augment training with unusual openings
+    Random,
+}
+
 impl GameWrapper {
-    pub fn new(width: i32, height: u32, snakes_count: u32) -> Self {
-        let mut snakes = Vec::new();
-        let mut rng = rand::thread_rng();
+    pub fn new(
+        width: i32,
+        height: i32,
+        snakes_count: u32,
+        mode: GameMode,
+        spawn: SnakeSpawn,
+        seed: u64,
+    ) -> Self {
+        let mut game_wrapper = Self {
+            turn: 0,
+            game: Game {
+                id: "0".to_string(),
+                ruleset: mode.ruleset(),
+                timeout: 1000,
+            },
+            board: Board {
+                height,
+                width,
+                food: Vec::new(),
+                snakes: Vec::new(),
+                hazards: Vec::new(),
+            },
+            eliminations: Vec::new(),
+            rng: StdRng::seed_from_u64(seed),
+            max_turns: DEFAULT_MAX_TURNS,
+            adjudication: Adjudication::default(),
+            enforce_timeout: false,
+            fast_board: None,
+            fast_eliminated: Vec::new(),
+        };
+
+        let fixed_spawn_coords = fixed_spawn_coords(width, &mut game_wrapper.rng);
+        let is_fixed_spawn = spawn == SnakeSpawn::Official
+            && FIXED_SPAWN_BOARD_SIZES.contains(&(width, height))
+            && snakes_count as usize <= fixed_spawn_coords.len();
 
         for i in 0..snakes_count {
-            snakes.push(Battlesnake {
+            let start = if is_fixed_spawn {
+                fixed_spawn_coords[i as usize]
+            } else {
+                game_wrapper.random_spawn_coord()
+            };
+
+            game_wrapper.board.snakes.push(Battlesnake {
                 id: i.to_string(),
                 name: format!("snake_{}", i),
-                health: 100,
-                body: Vec::new(),
-                head: Coord {
-                    x: rng.gen_range(0..width),
-                    y: rng.gen_range(0..height as i32),
-                },
+                health: SNAKE_MAX_HEALTH,
+                body: vec![start; SNAKE_STARTING_LENGTH as usize],
+                head: start,
                 latency: "0".to_string(),
                 length: SNAKE_STARTING_LENGTH,
                 shout: Some("".to_string()),
+                squad: if mode == GameMode::Squad {
+                    (i % SQUADS_COUNT).to_string()
+                } else {
+                    String::new()
+                },
             });
         }
 
-        let mut food = Vec::new();
-
-        for i in 0..=snakes_count {
-            food.push(random_coord(width, height));
+        if mode == GameMode::Constrictor {
+            // Constrictor games have no food
+        } else if is_fixed_spawn {
+            game_wrapper.place_food_fixed();
+        } else {
+            rules::place_food_randomly(
+                &mut game_wrapper.board,
+                snakes_count as i32,
+                &mut game_wrapper.rng,
+            );
         }
 
+        game_wrapper
+    }
+
+    /// Picks up a game from a state the engine sent, such as a recorded turn
+    pub fn from_game_state(game_state: &GameState, seed: u64) -> Self {
         Self {
-            turn: 0,
-            game: Game {
-                id: "0".to_string(),
-                ruleset: HashMap::new(),
-                timeout: 1000,
-            },
-            board: Board {
-                height,
-                width,
-                food,
-                snakes,
-                hazards: Vec::new(),
-            },
+            turn: game_state.turn,
+            game: game_state.game.clone(),
+            board: game_state.board.clone(),
+            eliminations: Vec::new(),
+            rng: StdRng::seed_from_u64(seed),
+            max_turns: DEFAULT_MAX_TURNS,
+            adjudication: Adjudication::default(),
+            enforce_timeout: false,
+            fast_board: None,
+            fast_eliminated: Vec::new(),
         }
     }
 
-    pub async fn play_for_outcome(&mut self, networks: &mut Vec<NeuralNetwork>) -> GameStepOutcome {
+    /// Plays the game out with one agent per snake, keyed by snake id
+    pub async fn play_for_outcome(
+        &mut self,
+        agents: &mut HashMap<String, Box<dyn Agent>>,
+    ) -> GameStepOutcome {
         #[cfg(feature = "visualize_game")]
         self.visualize();
-        #[cfg(debug_game)]
+        #[cfg(feature = "debug_game")]
         println!("Snakes: {:?}", self.board.snakes);
 
-        for _turn in self.turn.. {
-            let step_outcome = self.turn_step(networks);
-
-            match step_outcome {
-                GameStepOutcome::None => continue,
-                _ => return step_outcome,
+        for snake in &self.board.snakes {
+            if let Some(agent) = agents.get_mut(&snake.id) {
+                agent.start(&self.game, &self.turn, &self.board, snake);
             }
         }
 
-        GameStepOutcome::None
-    }
-
-    pub fn turn_step(&mut self, networks: &mut Vec<NeuralNetwork>) -> GameStepOutcome {
-        #[cfg(feature = "turn_logs")]
-        println!("Running turn {}:", self.turn);
-
-        self.age_snakes();
-        self.feed_snakes();
-        self.propagate_snakes(networks);
-        self.kill_snakes();
-
-        #[cfg(feature = "visualize_game")]
-        self.visualize();
-        #[cfg(debug_game)]
-        println!("Snakes: {:?}", self.board.snakes);
+        let outcome = loop {
+            let step_outcome = self.turn_step(agents);
 
-        self.turn += 1;
+            if step_outcome != GameStepOutcome::None {
+                break step_outcome;
+            }
+        };
 
-        match self.board.snakes.len() {
-            1 => GameStepOutcome::Winner(self.board.snakes[0].id.clone()),
-            0 => GameStepOutcome::Tie,
-            _ => GameStepOutcome::None,
-        }
-    }
+        // Eliminated snakes are told about the end as they were when eliminated
 
-    fn feed_snakes(&mut self) {
-        let mut snake_ids_by_head: HashMap<i32, String> = HashMap::new();
+        let snakes = self.board.snakes.iter().chain(
+            self.eliminations
+                .iter()
+                .map(|elimination| &elimination.snake),
+        );
 
-        for snake in &self.board.snakes {
-            let packed_coord = pack_coord(&snake.head, self.board.width);
-            snake_ids_by_head.insert(packed_coord, snake.id.clone());
+        for snake in snakes {
+            if let Some(agent) = agents.get_mut(&snake.id) {
+                agent.end(&self.game, &self.turn, &self.board, snake);
+            }
         }
 
-        for food in &self.board.food {
-            let packed_coord = pack_coord(food, self.board.width);
-            let Some(snake_id) = snake_ids_by_head.get(&packed_coord) else {
-                continue;
-            };
+        outcome
+    }
 
-            for snake in &mut self.board.snakes {
-                if &snake.id != snake_id {
-                    continue;
-                }
+    pub fn turn_step(&mut self, agents: &mut HashMap<String, Box<dyn Agent>>) -> GameStepOutcome {
+        #[cfg(feature = "turn_logs")]
+        println!("Running turn {}:", self.turn);
 
-                snake.length += 1;
-            }
-        }
+        let moves = self.choose_moves(agents);
+        self.apply_moves(&moves)
     }
 
-    fn propagate_snakes(&mut self, networks: &mut Vec<NeuralNetwork>) {
+    /// Asks each snake's agent for its move, in the same order as `board.snakes`, and records how
+    /// long it took as the snake's latency. With `enforce_timeout`, moves slower than `Game.timeout`
+    /// are missed, as on the engine
+    fn choose_moves(
+        &mut self,
+        agents: &mut HashMap<String, Box<dyn Agent>>,
+    ) -> Vec<Option<Direction>> {
+        let timeout = Duration::from_millis(self.game.timeout as u64);
         let mut moves = Vec::new();
-        let mut index = 0;
 
-        for snake in &self.board.snakes {
-            let Ok(id) = snake.id.parse::<usize>() else {
-                panic!("invalid snake id");
-            };
+        for i in 0..self.board.snakes.len() {
+            let snake = &self.board.snakes[i];
 
-            let Some(network) = &mut networks.get_mut(id) else {
-                panic!("invalid network");
+            let Some(agent) = agents.get_mut(&snake.id) else {
+                panic!("no agent for snake {}", snake.id);
             };
 
-            let chosen_move = choose_move(&self.game, &self.turn, &self.board, &snake, network);
-            moves.push((index, chosen_move));
+            let start = Instant::now();
+            let chosen_move = agent.get_move(&self.game, &self.turn, &self.board, snake);
+            let latency = start.elapsed();
 
-            index += 1;
-        }
+            self.board.snakes[i].latency = latency.as_millis().to_string();
 
-        // Move all snakes and grow if necessary
+            moves.push(chosen_move.filter(|_| !self.enforce_timeout || latency <= timeout));
+        }
 
-        for (index, chosen_move) in moves {
+        moves
+    }
 
-            let Some(chosen_move) = chosen_move else {
-                self.board.snakes.remove(index);
-                continue;
+    /// Resolves a turn from one move per snake, in the same order as `board.snakes`,
+    /// through the simulator's rules. Snakes without a move, because they timed out, answered
+    /// with nothing valid or come after the last of `moves`, repeat their last move like on the
+    /// engine. Boards that fit are stepped as a `FastBoard`, which plays out identically,
+    /// reusing the one from the last turn
+    pub fn apply_moves(&mut self, moves: &[Option<Direction>]) -> GameStepOutcome {
+        let ruleset = Ruleset::from_game(&self.game);
+        let geometry = self
+            .board
+            .geometry()
+            .with_wrapping(ruleset.mode == GameMode::Wrapped);
+
+        let moves: Vec<Direction> = self
+            .board
+            .snakes
+            .iter()
+            .enumerate()
+            .map(|(i, snake)| {
+                moves
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .unwrap_or_else(|| rules::last_move(snake, &geometry))
+            })
+            .collect();
+
+        if FastBoard::fits(&self.board) {
+            let fast_board = match &mut self.fast_board {
+                Some(fast_board) => {
+                    fast_board.load(&self.board);
+                    fast_board
+                }
+                None => self.fast_board.insert(FastBoard::from_board(&self.board)),
             };
 
-            let snake = &mut self.board.snakes[index];
-
-            let offset = match chosen_move {
-                "up" => Coord { x: 0, y: -1 },
-                "down" => Coord { x: 0, y: 1 },
-                "left" => Coord { x: -1, y: 0 },
-                "right" => Coord { x: 1, y: 0 },
-                _ => Coord { x: 0, y: 0 },
-            };
+            self.fast_eliminated.clear();
+            fast_board.step(
+                self.turn,
+                &ruleset,
+                &moves,
+                &mut self.rng,
+                &mut self.fast_eliminated,
+            );
+
+            for elimination in &self.fast_eliminated {
+                self.eliminations
+                    .push(elimination.to_elimination(&self.board));
+            }
 
-            assert_ne!(offset, Coord { x: 0, y: 0 }, "invalid move");
+            fast_board.update_board(&mut self.board);
+        } else {
+            let (board, events) =
+                rules::step(&self.board, self.turn, &ruleset, &moves, &mut self.rng);
 
-            let mut previous = snake.head.clone();
+            self.board = board;
 
-            snake.head.x += offset.x;
-            snake.head.y += offset.y;
+            for event in events {
+                if let Event::Eliminated(elimination) = event {
+                    self.eliminations.push(elimination);
+                }
+            }
+        }
 
-            for body_part in &mut snake.body {
-                let new_previous = body_part.clone();
+        #[cfg(feature = "visualize_game")]
+        self.visualize();
+        #[cfg(feature = "debug_game")]
+        println!("Snakes: {:?}", self.board.snakes);
 
-                body_part.x = previous.x;
-                body_part.y = previous.y;
+        self.turn += 1;
 
-                previous = new_previous;
+        if ruleset.mode == GameMode::Squad {
+            if let Some(snake) = self.board.snakes.first() {
+                if self
+                    .board
+                    .snakes
+                    .iter()
+                    .all(|other| other.squad == snake.squad)
+                {
+                    return GameStepOutcome::SquadWinner(snake.squad.clone());
+                }
             }
+        }
 
-            if (snake.body.len() as i32 + 1) < snake.length {
-                snake.body.push(previous.clone());
-            }
+        match self.board.snakes.len() {
+            1 => GameStepOutcome::Winner(self.board.snakes[0].id.clone()),
+            0 => GameStepOutcome::Tie,
+            _ if self.turn >= self.max_turns => GameStepOutcome::TurnLimit(self.adjudicate()),
+            _ => GameStepOutcome::None,
         }
     }
 
-    fn kill_snakes(&mut self) {
-        // Check out of bounds and kill snakes when necessary
-
-        self.board.snakes.retain(|snake| {
-            if is_out_of_bounds(
-                snake.head.x,
-                snake.head.y,
-                self.board.width,
-                self.board.height,
-            ) {
-                return false;
-            }
-
-            true
-        });
-
-        // check if the snake has collided with any snake's head that is not its own
-
-        // Check for head-on collisions and kill snakes when necessary
-
-        let mut snakes_to_kill = HashSet::new();
-        let mut snake_ids_by_head: HashMap<i32, String> = HashMap::new();
-
-        for snake in &self.board.snakes {
-            let packed_coord = pack_coord(&snake.head, self.board.width);
-
-            if let Some(other_snake_id) = snake_ids_by_head.get(&packed_coord) {
-                snakes_to_kill.insert(snake.id.clone());
-                snakes_to_kill.insert(other_snake_id.to_string());
-
-                continue;
+    /// The snake that wins a game stopped at its turn limit, if any
+    fn adjudicate(&self) -> Option<String> {
+        match self.adjudication {
+            Adjudication::Draw => None,
+            Adjudication::Rank => {
+                let mut ranked: Vec<&Battlesnake> = self.board.snakes.iter().collect();
+                ranked.sort_by_key(|snake| (-snake.length, -snake.health));
+
+                match ranked[..] {
+                    [first, second, ..]
+                        if (first.length, first.health) == (second.length, second.health) =>
+                    {
+                        None
+                    }
+                    [first, ..] => Some(first.id.clone()),
+                    [] => None,
+                }
             }
-
-            snake_ids_by_head.insert(packed_coord, snake.id.clone());
         }
+    }
 
-        // let mut snake_head_coords = HashSet::new(); // HashSet::from_iter(self.board.snakes.iter().map(|snake| snake.head));
-
-        // for snake in &self.board.snakes {
-        //     snake_head_coords.insert(snake.head);
-        // }
+    /// The ids of every snake that started in `squad`, eliminated or not
+    pub fn squad_snake_ids(&self, squad: &str) -> Vec<String> {
+        self.board
+            .snakes
+            .iter()
+            .chain(
+                self.eliminations
+                    .iter()
+                    .map(|elimination| &elimination.snake),
+            )
+            .filter(|snake| snake.squad == squad)
+            .map(|snake| snake.id.clone())
+            .collect()
+    }
 
-        // // Kill both snakes that collided their heads
-        // self.board.snakes.retain(|snake| {
-        //     if snake_head_coords.contains(&snake.head) {
-        //         return false;
-        //     }
+    pub fn mode(&self) -> GameMode {
+        Ruleset::from_game(&self.game).mode
+    }
 
-        //     true
-        // });
+    /// A random unoccupied cell with an even `x + y`, like the official engine's random spawns
+    fn random_spawn_coord(&mut self) -> Coord {
+        let spawn_coords: Vec<Coord> = rules::unoccupied_coords(&self.board)
+            .into_iter()
+            .filter(|coord| (coord.x + coord.y) % 2 == 0)
+            .collect();
 
-        // Check for body collisions and kill snakes when necessary
+        let Some(spawn_coord) = spawn_coords.choose(&mut self.rng) else {
+            panic!("no room to spawn snake");
+        };
 
-        let mut snake_body_coords = HashSet::new();
+        *spawn_coord
+    }
 
-        for snake in &self.board.snakes {
-            snake_body_coords.extend(snake.body.clone());
+    /// Places a food diagonal to each snake, on the side away from the center and never in a corner,
+    /// then one in the center. Small boards with more than 4 snakes only get the center food
+    fn place_food_fixed(&mut self) {
+        let center = Coord {
+            x: (self.board.width - 1) / 2,
+            y: (self.board.height - 1) / 2,
+        };
+        let is_small_board = self.board.width * self.board.height < 11 * 11;
+
+        if self.board.snakes.len() <= 4 || !is_small_board {
+            for snake in &self.board.snakes {
+                let head = snake.head;
+
+                let food_coords: Vec<Coord> = [(-1, -1), (-1, 1), (1, -1), (1, 1)]
+                    .into_iter()
+                    .map(|(x, y)| Coord {
+                        x: head.x + x,
+                        y: head.y + y,
+                    })
+                    .filter(|coord| {
+                        if *coord == center || self.board.food.contains(coord) {
+                            return false;
+                        }
+
+                        let is_away_from_center = (coord.x < head.x && head.x < center.x)
+                            || (center.x < head.x && head.x < coord.x)
+                            || (coord.y < head.y && head.y < center.y)
+                            || (center.y < head.y && head.y < coord.y);
+
+                        let is_corner = (coord.x == 0 || coord.x == self.board.width - 1)
+                            && (coord.y == 0 || coord.y == self.board.height - 1);
+
+                        is_away_from_center && !is_corner
+                    })
+                    .collect();
+
+                if let Some(food_coord) = food_coords.choose(&mut self.rng) {
+                    self.board.food.push(*food_coord);
+                }
+            }
         }
 
-        self.board.snakes.retain(|snake| {
-            if snake_body_coords.contains(&snake.head) {
-                return false;
-            };
-
-            true
-        });
-    }
+        let is_center_occupied = self.board.food.contains(&center)
+            || self
+                .board
+                .snakes
+                .iter()
+                .any(|snake| snake.body.contains(&center));
 
-    fn age_snakes(&mut self) {
-        for snake in &mut self.board.snakes {
-            snake.health -= 1;
+        if !is_center_occupied {
+            self.board.food.push(center);
         }
-
-        self.board.snakes.retain(|snake| snake.health > 0);
     }
 
     fn visualize(&self) {
-        let mut coord_types = Vec::new();
-
-        for x in 0..self.board.width {
-            for y in 0..self.board.height {
-                coord_types.push(CoordType::Empty);
-            }
-        }
+        let geometry = self
+            .board
+            .geometry()
+            .with_wrapping(self.mode() == GameMode::Wrapped);
+        let mut coord_types = vec![CoordType::Empty; geometry.area()];
 
         for food in &self.board.food {
-            coord_types[pack_coord(food, self.board.width) as usize] = CoordType::Food;
+            coord_types[geometry.pack(*food)] = CoordType::Food;
         }
 
         for snake in &self.board.snakes {
-            coord_types[pack_coord(&snake.head, self.board.width) as usize] = CoordType::Head;
+            coord_types[geometry.pack(snake.head)] = CoordType::Head;
 
             let mut previous = snake.head;
 
-            for body_part in &snake.body {
-                let direction = get_direction(*body_part, previous);
+            for body_part in snake.body.iter().skip(1) {
+                // Stacked body parts, such as a tail that just grew, share the cell of the part before them
 
-                coord_types[pack_coord(body_part, self.board.width) as usize] = match direction {
-                    "up" => CoordType::BodyUp,
-                    "down" => CoordType::BodyDown,
-                    "left" => CoordType::BodyLeft,
-                    "right" => CoordType::BodyRight,
-                    _ => {
-                        panic!("invalid direction, coordinates are possibly equal");
-                    }
+                if *body_part == previous {
+                    continue;
+                }
+
+                // Each body part points the way it lies from the part before it
+
+                let Some(direction) = Direction::ALL
+                    .into_iter()
+                    .find(|direction| geometry.neighbor(previous, *direction) == Some(*body_part))
+                else {
+                    panic!("body parts of snake {} aren't adjacent", snake.id);
+                };
+
+                coord_types[geometry.pack(*body_part)] = match direction {
+                    Direction::Up => CoordType::BodyUp,
+                    Direction::Down => CoordType::BodyDown,
+                    Direction::Left => CoordType::BodyLeft,
+                    Direction::Right => CoordType::BodyRight,
                 };
 
                 previous = *body_part;
@@ -321,17 +495,26 @@ impl GameWrapper {
         }
 
         for hazard in &self.board.hazards {
-            coord_types[pack_coord(hazard, self.board.width) as usize] = CoordType::Hazard;
+            coord_types[geometry.pack(*hazard)] = CoordType::Hazard;
         }
 
         println!("End of turn {}", self.turn);
 
-        for y in 0..self.board.height {
+        for elimination in &self.eliminations {
+            if elimination.turn != self.turn + 1 {
+                continue;
+            }
+
+            println!("Snake {} {}", elimination.snake.id, elimination.cause);
+        }
+
+        // Up is towards higher y, so the top row is printed first
+
+        for y in (0..self.board.height).rev() {
             let mut print_line = String::new();
 
             for x in 0..self.board.width {
-                let Some(coord_type) = coord_types.get(pack_xy(x, y, self.board.width) as usize)
-                else {
+                let Some(coord_type) = coord_types.get(geometry.pack(Coord { x, y })) else {
                     panic!("Out of bounds search");
                 };
 
@@ -353,3 +536,23 @@ impl GameWrapper {
         }
     }
 }
+
+/// The official spawn points of a square board: corners then edges or the other way around,
+/// with each group shuffled
+fn fixed_spawn_coords(width: i32, rng: &mut impl Rng) -> Vec<Coord> {
+    let min = 1;
+    let mid = (width - 1) / 2;
+    let max = width - 2;
+
+    let mut corners = [(min, min), (min, max), (max, min), (max, max)].map(|(x, y)| Coord { x, y });
+    let mut edges = [(min, mid), (mid, min), (mid, max), (max, mid)].map(|(x, y)| Coord { x, y });
+
+    corners.shuffle(rng);
+    edges.shuffle(rng);
+
+    if rng.gen_bool(0.5) {
+        return [corners, edges].concat();
+    }
+
+    [edges, corners].concat()
+}
@@ -0,0 +1,253 @@
+use std::mem;
+
+use crate::{Battlesnake, Board, Coord};
+
+use super::{
+    constants::SNAKE_MAX_HEALTH,
+    rules::{hazard_damage, Direction, GameMode, Ruleset},
+};
+
+/// What `make_move` changed, so `unmake_move` can restore the board it was given
+#[derive(Debug, Clone)]
+pub struct Unmake {
+    /// One per snake on the board before the move, in the same order
+    snakes: Vec<SnakeUnmake>,
+    /// Snakes in the order they were removed, each with its index at the time
+    removed_snakes: Vec<(usize, Battlesnake)>,
+    /// Eaten food with its index before any was removed
+    eaten_food: Vec<(usize, Coord)>,
+    /// Food removed by Constrictor after feeding
+    cleared_food: Vec<Coord>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SnakeUnmake {
+    tail: Coord,
+    health: i32,
+    length: i32,
+}
+
+/// Resolves a turn in place from one move per snake, in the same order as `board.snakes`,
+/// returning what is needed to undo it. Made for tree search, so it only applies the rules
+/// that don't depend on chance: no food is spawned and Royale hazards don't shrink.
+/// Otherwise it matches `rules::step`
+pub fn make_move(board: &mut Board, ruleset: &Ruleset, moves: &[Direction]) -> Unmake {
+    assert_eq!(
+        moves.len(),
+        board.snakes.len(),
+        "expected one move per snake"
+    );
+
+    let mut unmake = Unmake {
+        snakes: Vec::with_capacity(board.snakes.len()),
+        removed_snakes: Vec::new(),
+        eaten_food: Vec::new(),
+        cleared_food: Vec::new(),
+    };
+
+    // Move, then reduce health
+
+    let geometry = board.geometry();
+
+    for (snake, direction) in board.snakes.iter_mut().zip(moves) {
+        snake.head = direction.apply(snake.head);
+
+        if ruleset.mode == GameMode::Wrapped {
+            snake.head = geometry.wrap(snake.head);
+        }
+
+        snake.body.insert(0, snake.head);
+
+        let Some(tail) = snake.body.pop() else {
+            panic!("snake has no body");
+        };
+
+        unmake.snakes.push(SnakeUnmake {
+            tail,
+            health: snake.health,
+            length: snake.length,
+        });
+
+        snake.health -= 1;
+    }
+
+    // Damage hazards
+
+    for i in (0..board.snakes.len()).rev() {
+        let damage = hazard_damage(&board.food, &board.hazards, board.snakes[i].head, ruleset);
+
+        if damage == 0 {
+            continue;
+        }
+
+        let snake = &mut board.snakes[i];
+        snake.health = (snake.health - damage).max(0);
+
+        if snake.health == 0 {
+            unmake.removed_snakes.push((i, board.snakes.remove(i)));
+        }
+    }
+
+    // Feed
+
+    for (i, food) in board.food.iter().enumerate() {
+        let mut eaten = false;
+
+        for snake in &mut board.snakes {
+            if snake.head != *food {
+                continue;
+            }
+
+            eaten = true;
+            grow(snake);
+        }
+
+        if eaten {
+            unmake.eaten_food.push((i, *food));
+        }
+    }
+
+    for (i, _) in unmake.eaten_food.iter().rev() {
+        board.food.remove(*i);
+    }
+
+    if ruleset.mode == GameMode::Constrictor {
+        unmake.cleared_food = mem::take(&mut board.food);
+    }
+
+    // Eliminate
+
+    let eliminated = eliminated_snakes(board, ruleset);
+
+    for i in (0..board.snakes.len()).rev() {
+        if eliminated[i] {
+            unmake.removed_snakes.push((i, board.snakes.remove(i)));
+        }
+    }
+
+    match ruleset.mode {
+        GameMode::Constrictor => {
+            for snake in &mut board.snakes {
+                grow(snake);
+            }
+        }
+        GameMode::Squad => share_squad_attributes(board, ruleset, &mut unmake),
+        GameMode::Standard | GameMode::Royale | GameMode::Wrapped => {}
+    }
+
+    unmake
+}
+
+/// Restores the board `make_move` was given. Moves must be unmade in the reverse order they were made
+pub fn unmake_move(board: &mut Board, unmake: Unmake) {
+    if !unmake.cleared_food.is_empty() {
+        board.food = unmake.cleared_food;
+    }
+
+    for (i, food) in unmake.eaten_food {
+        board.food.insert(i, food);
+    }
+
+    for (i, snake) in unmake.removed_snakes.into_iter().rev() {
+        board.snakes.insert(i, snake);
+    }
+
+    for (snake, snake_unmake) in board.snakes.iter_mut().zip(unmake.snakes) {
+        snake.body.remove(0);
+
+        // Drop the tail copies added by growing
+
+        let growth = snake.length - snake_unmake.length;
+        snake.body.truncate(snake.body.len() - growth as usize);
+
+        snake.body.push(snake_unmake.tail);
+        snake.head = snake.body[0];
+        snake.health = snake_unmake.health;
+        snake.length = snake_unmake.length;
+    }
+}
+
+/// Matches `rules::share_squad_attributes`, with the removed squadmates recorded for undoing
+fn share_squad_attributes(board: &mut Board, ruleset: &Ruleset, unmake: &mut Unmake) {
+    if ruleset.shared_elimination {
+        for i in (0..board.snakes.len()).rev() {
+            let is_squad_eliminated = unmake
+                .removed_snakes
+                .iter()
+                .any(|(_, other)| other.squad == board.snakes[i].squad);
+
+            if is_squad_eliminated {
+                unmake.removed_snakes.push((i, board.snakes.remove(i)));
+            }
+        }
+    }
+
+    for i in 0..board.snakes.len() {
+        let (health, length) = board
+            .snakes
+            .iter()
+            .filter(|other| other.squad == board.snakes[i].squad)
+            .fold((0, 0), |(health, length), other| {
+                (health.max(other.health), length.max(other.length))
+            });
+
+        let snake = &mut board.snakes[i];
+
+        if ruleset.shared_health {
+            snake.health = health;
+        }
+
+        if ruleset.shared_length {
+            while snake.length < length {
+                snake.length += 1;
+
+                if let Some(tail) = snake.body.last().copied() {
+                    snake.body.push(tail);
+                }
+            }
+        }
+    }
+}
+
+fn grow(snake: &mut Battlesnake) {
+    snake.health = SNAKE_MAX_HEALTH;
+    snake.length += 1;
+
+    if let Some(tail) = snake.body.last().copied() {
+        snake.body.push(tail);
+    }
+}
+
+/// Which snakes `rules::eliminate_snakes` would remove, without working out why.
+/// Starved and out of bounds snakes are gone before collisions are checked
+fn eliminated_snakes(board: &Board, ruleset: &Ruleset) -> Vec<bool> {
+    let geometry = board.geometry();
+
+    let removed_first: Vec<bool> = board
+        .snakes
+        .iter()
+        .map(|snake| snake.health <= 0 || !geometry.contains(snake.head))
+        .collect();
+
+    board
+        .snakes
+        .iter()
+        .enumerate()
+        .map(|(i, snake)| {
+            if removed_first[i] {
+                return true;
+            }
+
+            board
+                .snakes
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| !removed_first[*j])
+                .any(|(j, other)| {
+                    ((j == i || !ruleset.is_passable(&snake.squad, &other.squad))
+                        && other.body[1..].contains(&snake.head))
+                        || (j != i && other.head == snake.head && other.length >= snake.length)
+                })
+        })
+        .collect()
+}
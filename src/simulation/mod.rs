@@ -1,3 +1,7 @@
-pub mod simulation;
+pub mod bitboard;
+pub mod constants;
 pub mod game;
-pub mod constants;
\ No newline at end of file
+pub mod make_move;
+pub mod rules;
+#[allow(clippy::module_inception)]
+pub mod simulation;
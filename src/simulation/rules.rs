@@ -0,0 +1,696 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use rand::{seq::SliceRandom, Rng};
+use serde_json::{json, Value};
+
+use crate::{geometry::Geometry, Battlesnake, Board, Coord, Game};
+
+use super::constants::{
+    DEFAULT_ALLOW_BODY_COLLISIONS, DEFAULT_FOOD_SPAWN_CHANCE, DEFAULT_HAZARD_DAMAGE_PER_TURN,
+    DEFAULT_MINIMUM_FOOD, DEFAULT_SHARED_ELIMINATION, DEFAULT_SHARED_HEALTH, DEFAULT_SHARED_LENGTH,
+    DEFAULT_SHRINK_EVERY_N_TURNS, SNAKE_MAX_HEALTH,
+};
+
+/// The official game modes the simulator can play, named as in `Game.ruleset`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum GameMode {
+    #[default]
+    Standard,
+    /// Standard rules with hazards closing in from a random edge every `shrinkEveryNTurns`
+    Royale,
+    /// No food, and every snake grows and has its health restored each turn
+    Constrictor,
+    /// Board edges wrap around, so moving off one side enters from the opposite side
+    Wrapped,
+    /// Snakes play in teams sharing a `squad` id, and the last squad standing wins
+    Squad,
+}
+
+impl GameMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Standard => "standard",
+            GameMode::Royale => "royale",
+            GameMode::Constrictor => "constrictor",
+            GameMode::Wrapped => "wrapped",
+            GameMode::Squad => "squad",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(GameMode::Standard),
+            "royale" => Some(GameMode::Royale),
+            "constrictor" => Some(GameMode::Constrictor),
+            "wrapped" => Some(GameMode::Wrapped),
+            "squad" => Some(GameMode::Squad),
+            _ => None,
+        }
+    }
+
+    /// The ruleset the official engine sends for this mode, with its default settings
+    pub fn ruleset(&self) -> HashMap<String, Value> {
+        HashMap::from([
+            ("name".to_string(), json!(self.name())),
+            (
+                "settings".to_string(),
+                json!({
+                    "foodSpawnChance": DEFAULT_FOOD_SPAWN_CHANCE,
+                    "minimumFood": DEFAULT_MINIMUM_FOOD,
+                    "hazardDamagePerTurn": DEFAULT_HAZARD_DAMAGE_PER_TURN,
+                    "royale": {
+                        "shrinkEveryNTurns": DEFAULT_SHRINK_EVERY_N_TURNS,
+                    },
+                    "squad": {
+                        "allowBodyCollisions": DEFAULT_ALLOW_BODY_COLLISIONS,
+                        "sharedElimination": DEFAULT_SHARED_ELIMINATION,
+                        "sharedHealth": DEFAULT_SHARED_HEALTH,
+                        "sharedLength": DEFAULT_SHARED_LENGTH,
+                    },
+                }),
+            ),
+        ])
+    }
+}
+
+/// The settings of `Game.ruleset` that affect how a turn is resolved
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ruleset {
+    pub mode: GameMode,
+    pub food_spawn_chance: i32,
+    pub minimum_food: i32,
+    pub hazard_damage_per_turn: i32,
+    pub shrink_every_n_turns: i32,
+    pub allow_body_collisions: bool,
+    pub shared_elimination: bool,
+    pub shared_health: bool,
+    pub shared_length: bool,
+}
+
+impl Ruleset {
+    /// Reads the ruleset the engine sent, using the official defaults for anything missing
+    pub fn from_game(game: &Game) -> Self {
+        let mode = game
+            .ruleset
+            .get("name")
+            .and_then(Value::as_str)
+            .and_then(GameMode::from_name)
+            .unwrap_or_default();
+
+        let settings = game.ruleset.get("settings");
+        let setting = |value: Option<&Value>, default: i32| {
+            value
+                .and_then(Value::as_i64)
+                .map_or(default, |value| value as i32)
+        };
+        let squad_setting = |key: &str, default: bool| {
+            settings
+                .and_then(|settings| settings.get("squad"))
+                .and_then(|squad| squad.get(key))
+                .and_then(Value::as_bool)
+                .unwrap_or(default)
+        };
+
+        Self {
+            mode,
+            food_spawn_chance: setting(
+                settings.and_then(|settings| settings.get("foodSpawnChance")),
+                DEFAULT_FOOD_SPAWN_CHANCE,
+            ),
+            minimum_food: setting(
+                settings.and_then(|settings| settings.get("minimumFood")),
+                DEFAULT_MINIMUM_FOOD,
+            ),
+            hazard_damage_per_turn: setting(
+                settings.and_then(|settings| settings.get("hazardDamagePerTurn")),
+                DEFAULT_HAZARD_DAMAGE_PER_TURN,
+            ),
+            shrink_every_n_turns: setting(
+                settings
+                    .and_then(|settings| settings.get("royale"))
+                    .and_then(|royale| royale.get("shrinkEveryNTurns")),
+                DEFAULT_SHRINK_EVERY_N_TURNS,
+            ),
+            allow_body_collisions: squad_setting(
+                "allowBodyCollisions",
+                DEFAULT_ALLOW_BODY_COLLISIONS,
+            ),
+            shared_elimination: squad_setting("sharedElimination", DEFAULT_SHARED_ELIMINATION),
+            shared_health: squad_setting("sharedHealth", DEFAULT_SHARED_HEALTH),
+            shared_length: squad_setting("sharedLength", DEFAULT_SHARED_LENGTH),
+        }
+    }
+
+    /// Whether `snake` can move through the body of `other` without being eliminated,
+    /// which squadmates can do when the ruleset allows it
+    pub fn is_passable(&self, snake_squad: &str, other_squad: &str) -> bool {
+        self.mode == GameMode::Squad && self.allow_body_collisions && snake_squad == other_squad
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// The name the API uses for this move
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::Left => "left",
+            Direction::Right => "right",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "up" => Some(Direction::Up),
+            "down" => Some(Direction::Down),
+            "left" => Some(Direction::Left),
+            "right" => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    /// The change in position from moving this way. Up is towards higher y
+    pub fn offset(&self) -> Coord {
+        match self {
+            Direction::Up => Coord { x: 0, y: 1 },
+            Direction::Down => Coord { x: 0, y: -1 },
+            Direction::Left => Coord { x: -1, y: 0 },
+            Direction::Right => Coord { x: 1, y: 0 },
+        }
+    }
+
+    pub fn apply(&self, coord: Coord) -> Coord {
+        let offset = self.offset();
+
+        Coord {
+            x: coord.x + offset.x,
+            y: coord.y + offset.y,
+        }
+    }
+}
+
+/// The move that brought `snake`'s head to where it is, which the engine repeats for snakes
+/// that don't answer in time. Snakes that haven't moved yet, with their body stacked, move up
+pub fn last_move(snake: &Battlesnake, geometry: &Geometry) -> Direction {
+    let Some(neck) = snake.body.get(1) else {
+        return Direction::Up;
+    };
+
+    Direction::ALL
+        .into_iter()
+        .find(|direction| geometry.neighbor(*neck, *direction) == Some(snake.head))
+        .unwrap_or(Direction::Up)
+}
+
+/// Why a snake was removed from the board
+#[derive(Debug, PartialEq, Clone)]
+pub enum EliminatedCause {
+    OutOfBounds,
+    SelfCollision,
+    /// Ran into the body of the snake with this id
+    BodyCollision(String),
+    /// Lost a head-on collision to the snake with this id
+    HeadToHead(String),
+    Starvation,
+    Hazard,
+    /// Eliminated along with a squadmate
+    SquadEliminated,
+}
+
+impl fmt::Display for EliminatedCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EliminatedCause::OutOfBounds => write!(f, "moved out of bounds"),
+            EliminatedCause::SelfCollision => write!(f, "collided with itself"),
+            EliminatedCause::BodyCollision(snake_id) => {
+                write!(f, "collided with the body of snake {}", snake_id)
+            }
+            EliminatedCause::HeadToHead(snake_id) => {
+                write!(f, "lost a head-to-head with snake {}", snake_id)
+            }
+            EliminatedCause::Starvation => write!(f, "starved"),
+            EliminatedCause::Hazard => write!(f, "was killed by hazards"),
+            EliminatedCause::SquadEliminated => write!(f, "was eliminated with its squad"),
+        }
+    }
+}
+
+/// A snake as it was when eliminated, with why and on which turn it happened
+#[derive(Debug, Clone)]
+pub struct Elimination {
+    pub snake: Battlesnake,
+    pub cause: EliminatedCause,
+    pub turn: i32,
+}
+
+/// Something that happened while resolving a turn
+#[derive(Debug, Clone)]
+pub enum Event {
+    Ate { snake_id: String, food: Coord },
+    FoodSpawned(Coord),
+    Eliminated(Elimination),
+}
+
+/// Resolves one turn of `board` from one move per snake, in the same order as `board.snakes`,
+/// returning the next board and what happened. The rules run in the Standard ruleset's order:
+/// move, reduce health, damage hazards, feed, spawn food, then eliminate.
+/// Constrictor removes food instead of spawning it and grows the survivors,
+/// Royale closes in its hazards for the next turn, and Squad shares attributes between squadmates
+pub fn step(
+    board: &Board,
+    turn: i32,
+    ruleset: &Ruleset,
+    moves: &[Direction],
+    rng: &mut impl Rng,
+) -> (Board, Vec<Event>) {
+    assert_eq!(
+        moves.len(),
+        board.snakes.len(),
+        "expected one move per snake"
+    );
+
+    let mut board = board.clone();
+    let mut events = Vec::new();
+
+    // Eliminations are recorded on the turn of the board they produce
+
+    let next_turn = turn + 1;
+
+    move_snakes(&mut board, moves, ruleset.mode);
+    reduce_snake_health(&mut board);
+    damage_hazards(&mut board, ruleset, next_turn, &mut events);
+    feed_snakes(&mut board, &mut events);
+
+    if ruleset.mode == GameMode::Constrictor {
+        board.food.clear();
+    } else {
+        spawn_food(&mut board, ruleset, rng, &mut events);
+    }
+
+    eliminate_snakes(&mut board, ruleset, next_turn, &mut events);
+
+    match ruleset.mode {
+        GameMode::Royale => shrink_hazards(&mut board, ruleset, next_turn, rng),
+        GameMode::Constrictor => grow_snakes(&mut board),
+        GameMode::Squad => share_squad_attributes(&mut board, ruleset, next_turn, &mut events),
+        GameMode::Standard | GameMode::Wrapped => {}
+    }
+
+    (board, events)
+}
+
+/// Moves every snake's head in its chosen direction, with the rest of the body following.
+/// In Wrapped mode heads leaving the board come back on the opposite side
+pub fn move_snakes(board: &mut Board, moves: &[Direction], mode: GameMode) {
+    let geometry = board.geometry();
+
+    for (snake, direction) in board.snakes.iter_mut().zip(moves) {
+        snake.head = direction.apply(snake.head);
+
+        if mode == GameMode::Wrapped {
+            snake.head = geometry.wrap(snake.head);
+        }
+
+        snake.body.insert(0, snake.head);
+        snake.body.pop();
+    }
+}
+
+pub fn reduce_snake_health(board: &mut Board) {
+    for snake in &mut board.snakes {
+        snake.health -= 1;
+    }
+}
+
+/// Hurts snakes whose heads are in a hazard, once for every hazard stacked on that cell.
+/// Snakes eating food this turn take no damage, and snakes brought to 0 health are eliminated
+pub fn damage_hazards(board: &mut Board, ruleset: &Ruleset, turn: i32, events: &mut Vec<Event>) {
+    let food = &board.food;
+    let hazards = &board.hazards;
+
+    board.snakes.retain_mut(|snake| {
+        let damage = hazard_damage(food, hazards, snake.head, ruleset);
+
+        if damage == 0 {
+            return true;
+        }
+
+        snake.health = (snake.health - damage).max(0);
+
+        if snake.health > 0 {
+            return true;
+        }
+
+        events.push(Event::Eliminated(Elimination {
+            snake: snake.clone(),
+            cause: EliminatedCause::Hazard,
+            turn,
+        }));
+
+        false
+    });
+}
+
+/// The damage a head at `head` takes from hazards this turn, which is none when it is eating
+pub fn hazard_damage(food: &[Coord], hazards: &[Coord], head: Coord, ruleset: &Ruleset) -> i32 {
+    if food.contains(&head) {
+        return 0;
+    }
+
+    let hazards_count = hazards.iter().filter(|hazard| **hazard == head).count() as i32;
+
+    ruleset.hazard_damage_per_turn * hazards_count
+}
+
+/// Snakes on food eat it, which restores their health and grows them by duplicating their tail.
+/// Eaten food is removed, even when several snakes share it
+pub fn feed_snakes(board: &mut Board, events: &mut Vec<Event>) {
+    board.food.retain(|food| {
+        let mut eaten = false;
+
+        for snake in &mut board.snakes {
+            if snake.head != *food {
+                continue;
+            }
+
+            eaten = true;
+
+            snake.health = SNAKE_MAX_HEALTH;
+            snake.length += 1;
+
+            if let Some(tail) = snake.body.last().copied() {
+                snake.body.push(tail);
+            }
+
+            events.push(Event::Ate {
+                snake_id: snake.id.clone(),
+                food: *food,
+            });
+        }
+
+        !eaten
+    });
+}
+
+/// Tops food up to the ruleset's `minimumFood`, otherwise has a `foodSpawnChance` percent chance to add one
+pub fn spawn_food(
+    board: &mut Board,
+    ruleset: &Ruleset,
+    rng: &mut impl Rng,
+    events: &mut Vec<Event>,
+) {
+    let food_count = board.food.len();
+
+    if (food_count as i32) < ruleset.minimum_food {
+        place_food_randomly(board, ruleset.minimum_food - food_count as i32, rng);
+    } else if ruleset.food_spawn_chance > 0
+        && 100 - rng.gen_range(0..100) < ruleset.food_spawn_chance
+    {
+        place_food_randomly(board, 1, rng);
+    }
+
+    for food in &board.food[food_count..] {
+        events.push(Event::FoodSpawned(*food));
+    }
+}
+
+pub fn place_food_randomly(board: &mut Board, count: i32, rng: &mut impl Rng) {
+    for _ in 0..count {
+        let unoccupied_coords = unoccupied_coords(board);
+
+        let Some(coord) = unoccupied_coords.choose(rng) else {
+            return;
+        };
+
+        board.food.push(*coord);
+    }
+}
+
+/// Cells that aren't food, hazards, snake bodies, or a cell a snake's head could move into next turn
+pub fn unoccupied_coords(board: &Board) -> Vec<Coord> {
+    let mut occupied_coords: HashSet<Coord> = HashSet::new();
+
+    occupied_coords.extend(&board.food);
+    occupied_coords.extend(&board.hazards);
+
+    for snake in &board.snakes {
+        occupied_coords.extend(&snake.body);
+
+        for direction in Direction::ALL {
+            occupied_coords.insert(direction.apply(snake.head));
+        }
+    }
+
+    let mut unoccupied_coords = Vec::new();
+
+    for x in 0..board.width {
+        for y in 0..board.height {
+            let coord = Coord { x, y };
+
+            if !occupied_coords.contains(&coord) {
+                unoccupied_coords.push(coord);
+            }
+        }
+    }
+
+    unoccupied_coords
+}
+
+/// Constrictor snakes grow and are fully healed every turn
+pub fn grow_snakes(board: &mut Board) {
+    for snake in &mut board.snakes {
+        snake.health = SNAKE_MAX_HEALTH;
+        snake.length += 1;
+
+        if let Some(tail) = snake.body.last().copied() {
+            snake.body.push(tail);
+        }
+    }
+}
+
+/// Every `shrinkEveryNTurns`, grows the hazards inward by one row or column from a random edge.
+/// `turn` is the turn the hazards are placed for. The safe area is always a rectangle,
+/// so it is recovered from the current hazards
+pub fn shrink_hazards(board: &mut Board, ruleset: &Ruleset, turn: i32, rng: &mut impl Rng) {
+    if ruleset.shrink_every_n_turns < 1 || turn % ruleset.shrink_every_n_turns != 0 {
+        return;
+    }
+
+    let hazards: HashSet<Coord> = board.hazards.iter().copied().collect();
+
+    let mut min_x = board.width;
+    let mut max_x = -1;
+    let mut min_y = board.height;
+    let mut max_y = -1;
+
+    for x in 0..board.width {
+        for y in 0..board.height {
+            if hazards.contains(&Coord { x, y }) {
+                continue;
+            }
+
+            min_x = min_x.min(x);
+            max_x = max_x.max(x);
+            min_y = min_y.min(y);
+            max_y = max_y.max(y);
+        }
+    }
+
+    // The whole board is already hazardous
+
+    if max_x < 0 {
+        return;
+    }
+
+    match rng.gen_range(0..4) {
+        0 if min_x < max_x => min_x += 1,
+        1 if max_x > min_x => max_x -= 1,
+        2 if min_y < max_y => min_y += 1,
+        3 if max_y > min_y => max_y -= 1,
+        _ => {}
+    }
+
+    board.hazards.clear();
+
+    for x in 0..board.width {
+        for y in 0..board.height {
+            if x < min_x || x > max_x || y < min_y || y > max_y {
+                board.hazards.push(Coord { x, y });
+            }
+        }
+    }
+}
+
+pub fn eliminate_snakes(board: &mut Board, ruleset: &Ruleset, turn: i32, events: &mut Vec<Event>) {
+    // Kill snakes that have starved or moved out of bounds. They are gone before collisions are checked
+
+    let geometry = board.geometry();
+
+    board.snakes.retain(|snake| {
+        let cause = if snake.health <= 0 {
+            EliminatedCause::Starvation
+        } else if !geometry.contains(snake.head) {
+            EliminatedCause::OutOfBounds
+        } else {
+            return true;
+        };
+
+        events.push(Event::Eliminated(Elimination {
+            snake: snake.clone(),
+            cause,
+            turn,
+        }));
+
+        false
+    });
+
+    // Find every collision before removing anyone, so snakes can eliminate each other
+
+    let mut snakes_by_body: HashMap<Coord, Vec<&Battlesnake>> = HashMap::new();
+    let mut snakes_by_head: HashMap<Coord, Vec<&Battlesnake>> = HashMap::new();
+
+    for snake in &board.snakes {
+        // The head is excluded so that heads meeting are treated as head-on collisions
+
+        for body_part in snake.body.iter().skip(1) {
+            snakes_by_body.entry(*body_part).or_default().push(snake);
+        }
+
+        snakes_by_head.entry(snake.head).or_default().push(snake);
+    }
+
+    let causes: Vec<Option<EliminatedCause>> = board
+        .snakes
+        .iter()
+        .map(|snake| {
+            // Check for body collisions, including with the snake's own body.
+            // Squadmates may be able to pass through each other
+
+            let body_owners = snakes_by_body
+                .get(&snake.head)
+                .map_or(&[][..], Vec::as_slice);
+
+            if body_owners.iter().any(|other| other.id == snake.id) {
+                return Some(EliminatedCause::SelfCollision);
+            }
+
+            if let Some(other) = body_owners
+                .iter()
+                .find(|other| !ruleset.is_passable(&snake.squad, &other.squad))
+            {
+                return Some(EliminatedCause::BodyCollision(other.id.clone()));
+            }
+
+            // Check for head-on collisions. A snake loses to any snake at least as long as it,
+            // so snakes of equal length all die
+
+            let longest_other = snakes_by_head[&snake.head]
+                .iter()
+                .filter(|other| other.id != snake.id)
+                .max_by_key(|other| other.length)?;
+
+            if longest_other.length >= snake.length {
+                return Some(EliminatedCause::HeadToHead(longest_other.id.clone()));
+            }
+
+            None
+        })
+        .collect();
+
+    let mut causes = causes.into_iter();
+
+    board.snakes.retain(|snake| {
+        let Some(cause) = causes.next().flatten() else {
+            return true;
+        };
+
+        events.push(Event::Eliminated(Elimination {
+            snake: snake.clone(),
+            cause,
+            turn,
+        }));
+
+        false
+    });
+}
+
+/// Applies what squadmates share, as the ruleset allows: squads that lost a snake this turn are
+/// eliminated together, and the rest take the health of their healthiest and grow to the length
+/// of their longest
+pub fn share_squad_attributes(
+    board: &mut Board,
+    ruleset: &Ruleset,
+    turn: i32,
+    events: &mut Vec<Event>,
+) {
+    if ruleset.shared_elimination {
+        let eliminated_squads: HashSet<String> = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Eliminated(elimination) if elimination.turn == turn => {
+                    Some(elimination.snake.squad.clone())
+                }
+                _ => None,
+            })
+            .collect();
+
+        board.snakes.retain(|snake| {
+            if !eliminated_squads.contains(&snake.squad) {
+                return true;
+            }
+
+            events.push(Event::Eliminated(Elimination {
+                snake: snake.clone(),
+                cause: EliminatedCause::SquadEliminated,
+                turn,
+            }));
+
+            false
+        });
+    }
+
+    let mut squad_health: HashMap<String, i32> = HashMap::new();
+    let mut squad_length: HashMap<String, i32> = HashMap::new();
+
+    for snake in &board.snakes {
+        let health = squad_health.entry(snake.squad.clone()).or_default();
+        *health = (*health).max(snake.health);
+
+        let length = squad_length.entry(snake.squad.clone()).or_default();
+        *length = (*length).max(snake.length);
+    }
+
+    for snake in &mut board.snakes {
+        if ruleset.shared_health {
+            snake.health = squad_health[&snake.squad];
+        }
+
+        if ruleset.shared_length {
+            while snake.length < squad_length[&snake.squad] {
+                snake.length += 1;
+
+                if let Some(tail) = snake.body.last().copied() {
+                    snake.body.push(tail);
+                }
+            }
+        }
+    }
+}
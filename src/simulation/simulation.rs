@@ -1,41 +1,76 @@
-use core::{net, panic};
-use std::{collections::HashSet, ops::Range, time::SystemTime};
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "benchmark_game")]
+use std::time::SystemTime;
 
-use rand::{prelude::SliceRandom, thread_rng};
+use rand::{prelude::SliceRandom, rngs::StdRng, Rng, SeedableRng};
 
 use crate::{
-    ml_snake::snake,
-    neural_network::{self, NeuralNetwork, NeuralNetworkManager},
-    simulation::game::GameStepOutcome, utils::build_neural_network,
+    geometry::Geometry,
+    ml_snake::{
+        agent::{Agent, NeuralNetworkAgent},
+        baselines::Baseline,
+    },
+    neural_network::{NeuralNetwork, NeuralNetworkManager},
+    simulation::game::GameStepOutcome,
+    utils::build_neural_network,
 };
 
-use super::game::GameWrapper;
+use super::{
+    constants::DEFAULT_MAX_TURNS,
+    game::{Adjudication, GameWrapper, SnakeSpawn},
+    rules::GameMode,
+};
+
+/// How a network did over a set of games
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Evaluation {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
 
 pub struct Simulation {
-    longest_length: u32,
-    highest_turns: u32,
+    pub longest_length: u32,
+    pub highest_turns: u32,
+    pub mode: GameMode,
+    pub spawn: SnakeSpawn,
+    /// Games still running after this many turns are stopped and adjudicated
+    pub max_turns: i32,
+    pub adjudication: Adjudication,
+    /// Scripted snakes added to every tournament game after the networks' snakes. Their wins
+    /// don't keep any network
+    pub opponents: Vec<Baseline>,
+    /// Seeds every game and network mutation, so the same seed replays a whole tournament
+    pub rng: StdRng,
 }
 
 impl Simulation {
-    pub fn new() -> Self {
+    pub fn new(seed: u64) -> Self {
         Self {
             longest_length: 0,
             highest_turns: 0,
+            mode: GameMode::Standard,
+            spawn: SnakeSpawn::Official,
+            max_turns: DEFAULT_MAX_TURNS,
+            adjudication: Adjudication::default(),
+            opponents: Vec::new(),
+            rng: StdRng::seed_from_u64(seed),
         }
     }
 
+    /// Plays each run of `snakes_count` networks against each other, one game per run, and keeps
+    /// only the networks whose snakes won. Returns how each game ended, in order
     pub async fn run_games(
         &mut self,
         games_count: u32,
         width: i32,
-        height: u32,
+        height: i32,
         snakes_count: u32,
         round: u32,
-        network_manager: &mut NeuralNetworkManager,
         networks: &mut Vec<NeuralNetwork>,
-    ) {
-
+    ) -> Vec<GameStepOutcome> {
         let mut winning_network_indexes = HashSet::new();
+        let mut game_outcomes = Vec::new();
 
         println!("Starting nets count {}", networks.len());
 
@@ -45,39 +80,96 @@ impl Simulation {
 
             println!("Starting game {}/{} of round {}", i + 1, games_count, round);
 
-            let Some(networks_for_game) = &mut networks.get_mut(
-                (i as usize * snakes_count as usize)
-                    ..(i as usize * snakes_count as usize + snakes_count as usize),
-            ) else {
+            // The index of the network playing snake "0" in this game
+            let first_network_index = i as usize * snakes_count as usize;
+
+            let Some(networks_for_game) = &mut networks
+                .get_mut(first_network_index..(first_network_index + snakes_count as usize))
+            else {
                 panic!("invalid networks splice");
             };
 
-            #[cfg(debug_game)]
+            #[cfg(feature = "debug_game")]
             println!("nets len {}", networks_for_game.len());
 
-            let mut game_wrapper = GameWrapper::new(width, height, snakes_count);
+            let mut game_wrapper =
+                self.new_game(width, height, snakes_count + self.opponents.len() as u32);
+
+            let mut agents: HashMap<String, Box<dyn Agent>> = HashMap::new();
+
+            for (snake, network) in game_wrapper
+                .board
+                .snakes
+                .iter()
+                .zip(networks_for_game.iter())
+            {
+                agents.insert(
+                    snake.id.clone(),
+                    Box::new(NeuralNetworkAgent::new(network.clone())),
+                );
+            }
+
+            for (snake, opponent) in game_wrapper
+                .board
+                .snakes
+                .iter()
+                .skip(snakes_count as usize)
+                .zip(&self.opponents)
+            {
+                agents.insert(snake.id.clone(), opponent.agent(self.rng.gen()));
+            }
 
-            let game_outcome = game_wrapper
-                .play_for_outcome(&mut networks_for_game.to_vec())
-                .await;
+            let game_outcome = game_wrapper.play_for_outcome(&mut agents).await;
 
             println!("{:?}", game_outcome);
 
-            match game_outcome {
+            for elimination in &game_wrapper.eliminations {
+                println!(
+                    "Snake {} {} on turn {}",
+                    elimination.snake.id, elimination.cause, elimination.turn
+                );
+            }
+
+            match &game_outcome {
                 GameStepOutcome::Winner(snake_id) => {
                     let Ok(relative_id) = snake_id.parse::<usize>() else {
                         panic!("invalid snake id");
                     };
 
-                    let absolute_id = relative_id + i as usize;
+                    // Opponents' wins don't keep any network
 
-                    winning_network_indexes.insert(absolute_id);
+                    if relative_id < snakes_count as usize {
+                        let absolute_id = first_network_index + relative_id;
+
+                        winning_network_indexes.insert(absolute_id);
+                    }
 
                     // let Some(network) = &mut networks.get_mut(id) else {
                     //     panic!("invalid network");
                     // };
                 }
-                GameStepOutcome::Tie => {}
+                GameStepOutcome::SquadWinner(squad) => {
+                    for snake_id in game_wrapper.squad_snake_ids(squad) {
+                        let Ok(relative_id) = snake_id.parse::<usize>() else {
+                            panic!("invalid snake id");
+                        };
+
+                        if relative_id < snakes_count as usize {
+                            winning_network_indexes.insert(first_network_index + relative_id);
+                        }
+                    }
+                }
+                // Adjudicated games count for the snake ranked first, but are reported separately
+                GameStepOutcome::TurnLimit(Some(snake_id)) => {
+                    let Ok(relative_id) = snake_id.parse::<usize>() else {
+                        panic!("invalid snake id");
+                    };
+
+                    if relative_id < snakes_count as usize {
+                        winning_network_indexes.insert(first_network_index + relative_id);
+                    }
+                }
+                GameStepOutcome::Tie | GameStepOutcome::TurnLimit(None) => {}
                 GameStepOutcome::None => {
                     panic!("invalid game outcome");
                 }
@@ -87,29 +179,34 @@ impl Simulation {
             let duration = SystemTime::now().duration_since(start).unwrap().as_millis();
             #[cfg(feature = "benchmark_game")]
             info!("game {} took {}ms", i + 1, duration);
+
+            game_outcomes.push(game_outcome);
         }
 
-        #[cfg(debug_simulation)]
+        #[cfg(feature = "debug_simulation")]
         println!("before retain {}", networks.len());
-        #[cfg(debug_simulation)]
+        #[cfg(feature = "debug_simulation")]
         println!("{:?}", winning_network_indexes);
 
         let mut i = 0;
         networks.retain(|_| {
+            let is_winner = winning_network_indexes.contains(&i);
             i += 1;
 
-            winning_network_indexes.contains(&i)
+            is_winner
         });
 
-        #[cfg(debug_simulation)]
+        #[cfg(feature = "debug_simulation")]
         println!("after retain {}", networks.len());
+
+        game_outcomes
     }
 
     pub async fn run_tournament(
         &mut self,
         games_count: u32,
         width: i32,
-        height: u32,
+        height: i32,
         snakes_count: u32,
         rounds_count: u32,
     ) {
@@ -119,8 +216,8 @@ impl Simulation {
         for _ in 0..games_count {
             for _ in 0..snakes_count {
                 let mut network = NeuralNetwork::new(&mut network_manager);
-                build_neural_network(&mut network, width, height);
-                network.mutate();
+                build_neural_network(&mut network, &Geometry::new(width, height));
+                network.mutate(&mut self.rng);
 
                 networks.push(network);
             }
@@ -133,21 +230,87 @@ impl Simulation {
                 height,
                 snakes_count,
                 round,
-                &mut network_manager,
                 &mut networks,
             )
             .await;
 
-            #[cfg(debug_simulation)]
+            #[cfg(feature = "debug_simulation")]
             println!("remaining networks count {}", networks.len());
 
-            self.reproduce_networks(&mut network_manager, &mut networks, games_count, snakes_count);
+            self.reproduce_networks(
+                &mut network_manager,
+                &mut networks,
+                games_count,
+                snakes_count,
+            );
             self.learn_networks(&mut networks);
         }
     }
 
+    /// Plays `network` against each of `opponents` in every game, as snake "0" with the
+    /// opponents after it, and tallies how it did
+    pub async fn evaluate(
+        &mut self,
+        network: &NeuralNetwork,
+        opponents: &[Baseline],
+        games_count: u32,
+        width: i32,
+        height: i32,
+    ) -> Evaluation {
+        let mut evaluation = Evaluation::default();
+
+        for _ in 0..games_count {
+            let mut game_wrapper = self.new_game(width, height, opponents.len() as u32 + 1);
+
+            let mut agents: HashMap<String, Box<dyn Agent>> = HashMap::new();
+            agents.insert(
+                "0".to_string(),
+                Box::new(NeuralNetworkAgent::new(network.clone())),
+            );
+
+            for (i, opponent) in opponents.iter().enumerate() {
+                agents.insert((i + 1).to_string(), opponent.agent(self.rng.gen()));
+            }
+
+            match game_wrapper.play_for_outcome(&mut agents).await {
+                GameStepOutcome::Winner(snake_id) | GameStepOutcome::TurnLimit(Some(snake_id))
+                    if snake_id == "0" =>
+                {
+                    evaluation.wins += 1
+                }
+                GameStepOutcome::SquadWinner(squad)
+                    if game_wrapper
+                        .squad_snake_ids(&squad)
+                        .contains(&"0".to_string()) =>
+                {
+                    evaluation.wins += 1
+                }
+                GameStepOutcome::Tie | GameStepOutcome::TurnLimit(None) => evaluation.draws += 1,
+                _ => evaluation.losses += 1,
+            }
+        }
+
+        evaluation
+    }
+
+    /// A new game with this simulation's settings
+    fn new_game(&mut self, width: i32, height: i32, snakes_count: u32) -> GameWrapper {
+        let mut game_wrapper = GameWrapper::new(
+            width,
+            height,
+            snakes_count,
+            self.mode,
+            self.spawn,
+            self.rng.gen(),
+        );
+        game_wrapper.max_turns = self.max_turns;
+        game_wrapper.adjudication = self.adjudication;
+
+        game_wrapper
+    }
+
     fn reproduce_networks(
-        &self,
+        &mut self,
         network_manager: &mut NeuralNetworkManager,
         networks: &mut Vec<NeuralNetwork>,
         games_count: u32,
@@ -162,21 +325,21 @@ impl Simulation {
                 panic!("invalid network");
             };
 
-            new_networks.push(network.clone(network_manager));
+            new_networks.push(network.clone_with_id(network_manager));
 
             i += 1;
         }
 
-        #[cfg(debug_simulation)]
+        #[cfg(feature = "debug_simulation")]
         println!("addtional networks count {}", new_networks.len());
 
         networks.extend(new_networks);
-        networks.shuffle(&mut thread_rng());
+        networks.shuffle(&mut self.rng);
     }
 
-    fn learn_networks(&self, networks: &mut Vec<NeuralNetwork>) {
+    fn learn_networks(&mut self, networks: &mut Vec<NeuralNetwork>) {
         for network in networks {
-            network.mutate();
+            network.mutate(&mut self.rng);
         }
     }
 }
@@ -1,22 +1,6 @@
 use rand::Rng;
 
-use crate::{neural_network::{Input, NeuralNetwork, Output}, Coord};
-
-pub fn pack_xy(x: i32, y: u32, width: i32) -> i32 {
-    x * width + y as i32
-}
-
-pub fn pack_coord(coord: &Coord, width: i32) -> i32 {
-    coord.x * width + coord.y
-}
-
-pub fn unpack_coord(packed_coord: i32, width: i32) -> Coord {
-
-    Coord {
-        x: packed_coord / width,
-        y: packed_coord % width,
-    }
-}
+use crate::{geometry::Geometry, neural_network::{Input, NeuralNetwork, Output}, simulation::rules::Direction, Coord};
 
 pub fn bool_as_f32(boolean: bool) -> f32 {
     if boolean {
@@ -26,43 +10,14 @@ pub fn bool_as_f32(boolean: bool) -> f32 {
     0.0
 }
 
-pub fn is_out_of_bounds(x: i32, y: i32, width: i32, height: u32) -> bool {
-    x < 0 || x >= width || y < 0 || y >= height as i32
-}
-
-pub fn get_direction<'a>(front: Coord, back: Coord) -> &'a str {
-    
-    // vertical
-    if front.x == back.x {
-        if front.y < back.y {
-            return "up"
-        }
-
-        return "down"
-    }
-
-    // horizontal
-    if front.y == back.y {
-        if front.x < back.x {
-            return "left"
-        }
-
-        return "right"
-    }
-
-    "unknown"
-}
-
-pub fn random_coord(width: i32, height: u32) -> Coord {
-    let mut rng = rand::thread_rng();
-
+pub fn random_coord(geometry: &Geometry, rng: &mut impl Rng) -> Coord {
     Coord {
-        x: rng.gen_range(0..width),
-        y: rng.gen_range(0..height) as i32,
+        x: rng.gen_range(0..geometry.width),
+        y: rng.gen_range(0..geometry.height),
     }
 }
 
-pub fn build_neural_network(neural_network: &mut NeuralNetwork, width: i32, height: u32) {
+pub fn build_neural_network(neural_network: &mut NeuralNetwork, geometry: &Geometry) {
     let mut inputs: Vec<Input> = vec![Input::new(
         "game".to_string(),
         vec![0., 0., 0., 0.],
@@ -74,40 +29,54 @@ pub fn build_neural_network(neural_network: &mut NeuralNetwork, width: i32, heig
         ],
     )];
 
-    for x in 0..width {
-        for y in 0..height {
+    // One input per move for the room it leaves, like `choose_move`
+
+    for _ in Direction::ALL {
+        inputs.push(Input::new(
+            "space".to_string(),
+            vec![0., 0., 0.],
+            vec!["s0".to_string(), "s1".to_string(), "s2".to_string()],
+        ))
+    }
 
-            inputs.push(Input::new(
-                "coord".to_string(),
-                vec![
-                    x as f32,
-                    y as f32,
-                    0.,
-                    0.,
-                    0.,
-                    0.,
-                    0.,
-                ],
-                vec![
-                    "c0".to_string(),
-                    "c1".to_string(),
-                    "c2".to_string(),
-                    "c3".to_string(),
-                    "c4".to_string(),
-                    "c5".to_string(),
-                    "c6".to_string(),
-                ],
-            ))
-        }
+    // One input per cell, in the same packed order `choose_move` builds its grid in
+
+    for coord in geometry.coords() {
+        inputs.push(Input::new(
+            "coord".to_string(),
+            vec![
+                coord.x as f32,
+                coord.y as f32,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+            ],
+            vec![
+                "c0".to_string(),
+                "c1".to_string(),
+                "c2".to_string(),
+                "c3".to_string(),
+                "c4".to_string(),
+                "c5".to_string(),
+                "c6".to_string(),
+                "c7".to_string(),
+                "c8".to_string(),
+            ],
+        ))
     }
 
-    // Outputs
+    // Outputs: a score for each move, then how good the board is
 
     let outputs: Vec<Output> = vec![
         Output::new("up".to_string()),
         Output::new("down".to_string()),
         Output::new("left".to_string()),
         Output::new("right".to_string()),
+        Output::new("value".to_string()),
     ];
 
     neural_network.build(&inputs, outputs.len());
@@ -0,0 +1,117 @@
+mod common;
+
+use ml_battle_snake::{
+    geometry::Geometry,
+    ml_snake::baselines::Baseline,
+    neural_network::{NeuralNetwork, NeuralNetworkManager},
+    simulation::{
+        rules::Direction::{self, Down, Left, Right, Up},
+        simulation::Simulation,
+    },
+    utils::build_neural_network,
+    Board,
+};
+
+use common::{board, coord, game, pocket_board, snake};
+
+fn get_move(baseline: Baseline, board: &Board) -> Option<Direction> {
+    baseline
+        .agent(0)
+        .get_move(&game(), &0, board, &board.snakes[0])
+}
+
+#[test]
+fn names_round_trip() {
+    for baseline in Baseline::ALL {
+        assert_eq!(Baseline::from_name(baseline.name()), Some(baseline));
+    }
+}
+
+#[test]
+fn greedy_food_heads_for_the_closest_food() {
+    let board = board(
+        vec![snake("a", &[(5, 5), (5, 4), (5, 3)])],
+        vec![coord(1, 5), coord(8, 5)],
+    );
+
+    assert_eq!(get_move(Baseline::GreedyFood, &board), Some(Right));
+}
+
+#[test]
+fn tail_chaser_follows_its_tail() {
+    let board = board(
+        vec![snake("a", &[(5, 5), (5, 4), (4, 4), (4, 5), (4, 6)])],
+        vec![coord(5, 8)],
+    );
+
+    assert_eq!(get_move(Baseline::TailChaser, &board), Some(Up));
+}
+
+#[test]
+fn flood_fill_avoids_dead_ends() {
+    let board = pocket_board(vec![]);
+
+    assert_eq!(get_move(Baseline::FloodFill, &board), Some(Left));
+}
+
+#[test]
+fn head_hunter_chases_shorter_snakes() {
+    let board = board(
+        vec![
+            snake("a", &[(5, 5), (5, 4), (5, 3), (5, 2)]),
+            snake("b", &[(2, 5), (1, 5), (0, 5)]),
+        ],
+        vec![coord(9, 5)],
+    );
+
+    assert_eq!(get_move(Baseline::HeadHunter, &board), Some(Left));
+}
+
+#[test]
+fn head_hunter_eats_when_nobody_is_shorter() {
+    let board = board(
+        vec![
+            snake("a", &[(5, 5), (5, 4), (5, 3)]),
+            snake("b", &[(2, 5), (1, 5), (0, 5)]),
+        ],
+        vec![coord(9, 5)],
+    );
+
+    assert_eq!(get_move(Baseline::HeadHunter, &board), Some(Right));
+}
+
+#[test]
+fn random_safe_is_safe_and_repeatable() {
+    let board = board(vec![snake("a", &[(0, 10), (1, 10), (2, 10)])], vec![]);
+
+    for seed in 0..20 {
+        let game = game();
+
+        let mut agent = Baseline::RandomSafe.agent(seed);
+        let mut replay = Baseline::RandomSafe.agent(seed);
+
+        let chosen_move = agent.get_move(&game, &0, &board, &board.snakes[0]);
+
+        assert_eq!(chosen_move, Some(Down));
+        assert_eq!(
+            chosen_move,
+            replay.get_move(&game, &0, &board, &board.snakes[0])
+        );
+    }
+}
+
+#[tokio::test]
+async fn networks_are_evaluated_against_baselines() {
+    let mut simulation = Simulation::new(0);
+    simulation.max_turns = 50;
+
+    let mut network_manager = NeuralNetworkManager::new();
+    let mut network = NeuralNetwork::new(&mut network_manager);
+    build_neural_network(&mut network, &Geometry::new(11, 11));
+
+    let evaluation = simulation
+        .evaluate(&network, &[Baseline::FloodFill], 3, 11, 11)
+        .await;
+
+    assert_eq!(evaluation.wins + evaluation.losses + evaluation.draws, 3);
+}
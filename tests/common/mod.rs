@@ -0,0 +1,76 @@
+// Each test crate uses only some of these
+#![allow(dead_code)]
+
+use ml_battle_snake::{
+    simulation::{constants::SNAKE_MAX_HEALTH, rules::GameMode},
+    Battlesnake, Board, Coord, Game,
+};
+
+pub fn coord(x: i32, y: i32) -> Coord {
+    Coord { x, y }
+}
+
+/// A snake with full health and `body` from head to tail
+pub fn snake(id: &str, body: &[(i32, i32)]) -> Battlesnake {
+    snake_with_health(id, body, SNAKE_MAX_HEALTH)
+}
+
+/// A snake with `health` and `body` from head to tail
+pub fn snake_with_health(id: &str, body: &[(i32, i32)], health: i32) -> Battlesnake {
+    let body: Vec<Coord> = body.iter().map(|&(x, y)| coord(x, y)).collect();
+
+    Battlesnake {
+        id: id.to_string(),
+        name: id.to_string(),
+        health,
+        head: body[0],
+        length: body.len() as i32,
+        body,
+        latency: "0".to_string(),
+        shout: None,
+        squad: String::new(),
+    }
+}
+
+/// A Standard game
+pub fn game() -> Game {
+    Game {
+        id: "test".to_string(),
+        ruleset: GameMode::Standard.ruleset(),
+        timeout: 500,
+    }
+}
+
+/// An 11x11 board without hazards
+pub fn board(snakes: Vec<Battlesnake>, food: Vec<Coord>) -> Board {
+    Board {
+        height: 11,
+        width: 11,
+        food,
+        snakes,
+        hazards: Vec::new(),
+    }
+}
+
+/// Moving up from `a`'s head at (5, 5) leads into a pocket of one cell walled off by `b`
+pub fn pocket_board(food: Vec<Coord>) -> Board {
+    board(
+        vec![
+            snake("a", &[(5, 5), (5, 4), (5, 3)]),
+            snake(
+                "b",
+                &[
+                    (8, 6),
+                    (7, 6),
+                    (6, 6),
+                    (6, 7),
+                    (5, 7),
+                    (4, 7),
+                    (4, 6),
+                    (3, 6),
+                ],
+            ),
+        ],
+        food,
+    )
+}
@@ -0,0 +1,93 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use ml_battle_snake::{
+    simulation::{game::GameWrapper, rules::Direction},
+    Board, Coord, GameState,
+};
+use serde::Deserialize;
+
+/// A hand-built turn: the state it starts from, each snake's move, and the board the official
+/// rules say it should produce, worked out by hand rather than recorded from the engine
+#[derive(Deserialize)]
+struct Fixture {
+    description: String,
+    state: GameState,
+    moves: HashMap<String, String>,
+    expected: Board,
+}
+
+/// The board with food, hazards and snakes in a fixed order, so boards can be compared regardless
+/// of the order they were listed in
+fn normalize(board: &Board) -> serde_json::Value {
+    let sorted = |coords: &[Coord]| {
+        let mut coords: Vec<(i32, i32)> = coords.iter().map(|coord| (coord.x, coord.y)).collect();
+        coords.sort();
+        coords
+    };
+
+    let mut snakes = board.snakes.clone();
+    snakes.sort_by(|a, b| a.id.cmp(&b.id));
+
+    serde_json::json!({
+        "width": board.width,
+        "height": board.height,
+        "food": sorted(&board.food),
+        "hazards": sorted(&board.hazards),
+        "snakes": snakes,
+    })
+}
+
+#[test]
+fn simulator_matches_rules_fixtures() {
+    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/rules");
+
+    let mut paths: Vec<_> = fs::read_dir(&fixtures_dir)
+        .expect("fixtures directory should exist")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    paths.sort();
+
+    assert!(!paths.is_empty(), "no fixtures found");
+
+    let mut failures = Vec::new();
+
+    for path in &paths {
+        let fixture: Fixture = serde_json::from_str(&fs::read_to_string(path).unwrap())
+            .unwrap_or_else(|error| panic!("invalid fixture {}: {}", path.display(), error));
+
+        let mut game = GameWrapper::from_game_state(&fixture.state, 0);
+
+        let moves: Vec<Option<Direction>> = game
+            .board
+            .snakes
+            .iter()
+            .map(|snake| {
+                fixture
+                    .moves
+                    .get(&snake.id)
+                    .and_then(|name| Direction::from_name(name))
+            })
+            .collect();
+
+        game.apply_moves(&moves);
+
+        let actual = normalize(&game.board);
+        let expected = normalize(&fixture.expected);
+
+        if actual != expected {
+            failures.push(format!(
+                "{} ({})\n  expected: {}\n  actual:   {}",
+                path.file_name().unwrap().to_string_lossy(),
+                fixture.description,
+                expected,
+                actual
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use ml_battle_snake::{
+    geometry::Geometry,
+    ml_snake::agent::{Agent, NeuralNetworkAgent},
+    neural_network::{NeuralNetwork, NeuralNetworkManager},
+    simulation::{
+        game::{GameWrapper, SnakeSpawn},
+        rules::{Direction, GameMode},
+    },
+    utils::build_neural_network,
+    Coord,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+#[test]
+fn packing_covers_rectangular_boards_exactly_once() {
+    let geometry = Geometry::new(7, 11);
+
+    let indexes: Vec<usize> = geometry
+        .coords()
+        .map(|coord| geometry.pack(coord))
+        .collect();
+
+    assert_eq!(indexes, (0..77).collect::<Vec<usize>>());
+    assert_eq!(
+        geometry.unpack(geometry.pack(Coord { x: 6, y: 10 })),
+        Coord { x: 6, y: 10 }
+    );
+    assert!(geometry.contains(Coord { x: 6, y: 10 }));
+    assert!(!geometry.contains(Coord { x: 7, y: 0 }));
+    assert!(!geometry.contains(Coord { x: 0, y: 11 }));
+}
+
+#[test]
+fn neighbors_stop_at_edges_unless_wrapped() {
+    let geometry = Geometry::new(7, 11);
+    let corner = Coord { x: 6, y: 10 };
+
+    let neighbors: Vec<(Direction, Coord)> = geometry.neighbors(corner).collect();
+
+    assert_eq!(
+        neighbors,
+        vec![
+            (Direction::Down, Coord { x: 6, y: 9 }),
+            (Direction::Left, Coord { x: 5, y: 10 }),
+        ]
+    );
+
+    let wrapped = geometry.with_wrapping(true);
+
+    assert_eq!(
+        wrapped.neighbor(corner, Direction::Up),
+        Some(Coord { x: 6, y: 0 })
+    );
+    assert_eq!(
+        wrapped.neighbor(corner, Direction::Right),
+        Some(Coord { x: 0, y: 10 })
+    );
+}
+
+#[test]
+fn networks_play_on_rectangular_boards() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut network_manager = NeuralNetworkManager::new();
+    let mut game = GameWrapper::new(7, 11, 2, GameMode::Standard, SnakeSpawn::Random, 0);
+
+    let mut agents: HashMap<String, Box<dyn Agent>> = HashMap::new();
+
+    for snake in &game.board.snakes {
+        let mut network = NeuralNetwork::new(&mut network_manager);
+        build_neural_network(&mut network, &game.board.geometry());
+        network.mutate(&mut rng);
+
+        agents.insert(snake.id.clone(), Box::new(NeuralNetworkAgent::new(network)));
+    }
+
+    for _ in 0..3 {
+        game.turn_step(&mut agents);
+    }
+
+    assert_eq!(game.turn, 3);
+}
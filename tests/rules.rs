@@ -0,0 +1,1313 @@
+mod common;
+
+use std::collections::HashMap;
+
+use ml_battle_snake::{
+    geometry::Geometry,
+    ml_snake::agent::Agent,
+    simulation::{
+        bitboard::FastBoard,
+        game::{Adjudication, GameStepOutcome, GameWrapper, SnakeSpawn},
+        make_move::{make_move, unmake_move},
+        rules::{
+            self,
+            Direction::{Down, Left, Right, Up},
+            EliminatedCause, GameMode, Ruleset,
+        },
+    },
+    Battlesnake, Board, Coord, Game,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde_json::json;
+
+use common::{coord, snake, snake_with_health};
+
+fn squad_snake(id: &str, squad: &str, body: &[(i32, i32)], health: i32) -> Battlesnake {
+    Battlesnake {
+        squad: squad.to_string(),
+        ..snake_with_health(id, body, health)
+    }
+}
+
+fn squad_game(snakes: Vec<Battlesnake>) -> GameWrapper {
+    let mut game = game(snakes, vec![], vec![]);
+    game.game.ruleset = GameMode::Squad.ruleset();
+    set_setting(&mut game, "minimumFood", json!(0));
+    set_setting(&mut game, "foodSpawnChance", json!(0));
+
+    game
+}
+
+fn game(snakes: Vec<Battlesnake>, food: Vec<Coord>, hazards: Vec<Coord>) -> GameWrapper {
+    let mut game = GameWrapper::new(11, 11, 0, GameMode::Standard, SnakeSpawn::Random, 0);
+
+    game.game = Game {
+        id: "test".to_string(),
+        ruleset: HashMap::from([
+            ("name".to_string(), json!("standard")),
+            (
+                "settings".to_string(),
+                json!({ "foodSpawnChance": 0, "minimumFood": 0, "hazardDamagePerTurn": 14 }),
+            ),
+        ]),
+        timeout: 500,
+    };
+    game.board = Board {
+        height: 11,
+        width: 11,
+        food,
+        snakes,
+        hazards,
+    };
+    game.rng = StdRng::seed_from_u64(0);
+    game.max_turns = 1000;
+    game.adjudication = Adjudication::Draw;
+
+    game
+}
+
+fn set_setting(game: &mut GameWrapper, key: &str, value: serde_json::Value) {
+    game.game.ruleset.get_mut("settings").unwrap()[key] = value;
+}
+
+fn ruleset(game: &GameWrapper) -> Ruleset {
+    Ruleset::from_game(&game.game)
+}
+
+fn spawn_food(game: &mut GameWrapper) {
+    let ruleset = ruleset(game);
+    rules::spawn_food(&mut game.board, &ruleset, &mut game.rng, &mut Vec::new());
+}
+
+/// Shrinks hazards ahead of the turn after `game.turn`, as at the end of a Royale turn
+fn shrink_hazards(game: &mut GameWrapper) {
+    let ruleset = ruleset(game);
+    rules::shrink_hazards(&mut game.board, &ruleset, game.turn + 1, &mut game.rng);
+}
+
+fn body_of(game: &GameWrapper, id: &str) -> Vec<Coord> {
+    game.board
+        .snakes
+        .iter()
+        .find(|snake| snake.id == id)
+        .expect("snake should be alive")
+        .body
+        .clone()
+}
+
+#[test]
+fn moves_follow_official_directions() {
+    for (direction, expected_head) in [
+        (Up, coord(5, 6)),
+        (Down, coord(5, 4)),
+        (Left, coord(4, 5)),
+        (Right, coord(6, 5)),
+    ] {
+        let mut game = game(vec![snake("a", &[(5, 5), (5, 5), (5, 5)])], vec![], vec![]);
+
+        rules::move_snakes(&mut game.board, &[direction], GameMode::Standard);
+
+        assert_eq!(
+            game.board.snakes[0].head, expected_head,
+            "moving {:?}",
+            direction
+        );
+        assert_eq!(game.board.snakes[0].body[0], expected_head);
+    }
+}
+
+#[test]
+fn body_follows_head_and_tail_moves_away() {
+    let mut game = game(vec![snake("a", &[(5, 5), (5, 4), (5, 3)])], vec![], vec![]);
+
+    rules::move_snakes(&mut game.board, &[Right], GameMode::Standard);
+
+    assert_eq!(
+        body_of(&game, "a"),
+        vec![coord(6, 5), coord(5, 5), coord(5, 4)]
+    );
+}
+
+#[test]
+fn health_drops_by_one_each_turn() {
+    let mut game = game(
+        vec![snake_with_health("a", &[(5, 5), (5, 4), (5, 3)], 50)],
+        vec![],
+        vec![],
+    );
+
+    game.apply_moves(&[Some(Up)]);
+
+    assert_eq!(game.board.snakes[0].health, 49);
+}
+
+#[test]
+fn hazard_damage_stacks_and_is_skipped_when_eating() {
+    let mut game = game(
+        vec![
+            snake("a", &[(1, 1), (1, 0), (0, 0)]),
+            snake("b", &[(8, 8), (8, 7), (8, 6)]),
+        ],
+        vec![coord(8, 8)],
+        vec![coord(1, 1), coord(1, 1), coord(8, 8)],
+    );
+
+    let ruleset = ruleset(&game);
+    rules::damage_hazards(&mut game.board, &ruleset, 1, &mut Vec::new());
+
+    assert_eq!(game.board.snakes[0].health, 100 - 28);
+    assert_eq!(game.board.snakes[1].health, 100);
+}
+
+#[test]
+fn hazard_damage_happens_after_moving() {
+    let mut game = game(
+        vec![snake_with_health("a", &[(1, 1), (1, 0), (0, 0)], 15)],
+        vec![],
+        vec![coord(1, 2)],
+    );
+
+    let outcome = game.apply_moves(&[Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::Tie);
+    assert!(game.board.snakes.is_empty());
+}
+
+#[test]
+fn snakes_starve_after_moving() {
+    let mut game = game(
+        vec![
+            snake_with_health("a", &[(1, 1), (1, 0), (0, 0)], 1),
+            snake("b", &[(8, 8), (8, 7), (8, 6)]),
+        ],
+        vec![],
+        vec![],
+    );
+
+    let outcome = game.apply_moves(&[Some(Up), Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::Winner("b".to_string()));
+}
+
+#[test]
+fn moving_out_of_bounds_eliminates() {
+    let mut game = game(
+        vec![
+            snake("a", &[(0, 5), (1, 5), (2, 5)]),
+            snake("b", &[(8, 8), (8, 7), (8, 6)]),
+        ],
+        vec![],
+        vec![],
+    );
+
+    let outcome = game.apply_moves(&[Some(Left), Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::Winner("b".to_string()));
+}
+
+#[test]
+fn chasing_own_tail_is_safe() {
+    let mut game = game(
+        vec![
+            snake("a", &[(5, 5), (5, 4), (4, 4), (4, 5)]),
+            snake("b", &[(8, 8), (8, 7), (8, 6)]),
+        ],
+        vec![],
+        vec![],
+    );
+
+    let outcome = game.apply_moves(&[Some(Left), Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::None);
+    assert_eq!(
+        body_of(&game, "a"),
+        vec![coord(4, 5), coord(5, 5), coord(5, 4), coord(4, 4)]
+    );
+}
+
+#[test]
+fn chasing_another_snakes_tail_is_safe() {
+    let mut game = game(
+        vec![
+            snake("a", &[(3, 5), (2, 5), (1, 5)]),
+            snake("b", &[(5, 5), (5, 4), (4, 4)]),
+        ],
+        vec![],
+        vec![],
+    );
+
+    let outcome = game.apply_moves(&[Some(Right), Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::None);
+    assert_eq!(game.board.snakes.len(), 2);
+}
+
+#[test]
+fn body_collision_eliminates_only_the_attacker() {
+    let mut game = game(
+        vec![
+            snake("a", &[(4, 6), (3, 6), (2, 6)]),
+            snake("b", &[(5, 7), (5, 6), (5, 5), (5, 4)]),
+        ],
+        vec![],
+        vec![],
+    );
+
+    let outcome = game.apply_moves(&[Some(Right), Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::Winner("b".to_string()));
+}
+
+#[test]
+fn self_collision_eliminates() {
+    let mut game = game(
+        vec![
+            snake("a", &[(5, 5), (5, 4), (4, 4), (4, 5), (4, 6)]),
+            snake("b", &[(8, 8), (8, 7), (8, 6)]),
+        ],
+        vec![],
+        vec![],
+    );
+
+    let outcome = game.apply_moves(&[Some(Left), Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::Winner("b".to_string()));
+}
+
+#[test]
+fn snakes_eliminated_before_collisions_cannot_be_collided_with() {
+    // b starves this turn, so a moving into b's body survives
+    let mut game = game(
+        vec![
+            snake("a", &[(4, 6), (3, 6), (2, 6)]),
+            snake_with_health("b", &[(5, 7), (5, 6), (5, 5), (5, 4)], 1),
+        ],
+        vec![],
+        vec![],
+    );
+
+    let outcome = game.apply_moves(&[Some(Right), Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::Winner("a".to_string()));
+}
+
+#[test]
+fn head_to_head_eliminates_the_shorter_snake() {
+    let mut game = game(
+        vec![
+            snake("a", &[(4, 5), (3, 5), (2, 5), (1, 5)]),
+            snake("b", &[(6, 5), (7, 5), (8, 5)]),
+        ],
+        vec![],
+        vec![],
+    );
+
+    let outcome = game.apply_moves(&[Some(Right), Some(Left)]);
+
+    assert_eq!(outcome, GameStepOutcome::Winner("a".to_string()));
+    assert_eq!(body_of(&game, "a")[0], coord(5, 5));
+}
+
+#[test]
+fn head_to_head_between_equal_lengths_eliminates_both() {
+    let mut game = game(
+        vec![
+            snake("a", &[(4, 5), (3, 5), (2, 5)]),
+            snake("b", &[(6, 5), (7, 5), (8, 5)]),
+        ],
+        vec![],
+        vec![],
+    );
+
+    let outcome = game.apply_moves(&[Some(Right), Some(Left)]);
+
+    assert_eq!(outcome, GameStepOutcome::Tie);
+}
+
+#[test]
+fn three_way_head_to_head_leaves_only_the_strictly_longest() {
+    let mut game = game(
+        vec![
+            snake("a", &[(4, 5), (3, 5), (2, 5), (1, 5)]),
+            snake("b", &[(6, 5), (7, 5), (8, 5)]),
+            snake("c", &[(5, 6), (5, 7), (5, 8)]),
+        ],
+        vec![],
+        vec![],
+    );
+
+    let outcome = game.apply_moves(&[Some(Right), Some(Left), Some(Down)]);
+
+    assert_eq!(outcome, GameStepOutcome::Winner("a".to_string()));
+}
+
+#[test]
+fn eating_removes_food_restores_health_and_duplicates_tail() {
+    let mut game = game(
+        vec![snake_with_health("a", &[(5, 5), (5, 4), (5, 3)], 40)],
+        vec![coord(5, 6), coord(0, 0)],
+        vec![],
+    );
+
+    game.apply_moves(&[Some(Up)]);
+
+    let snake = &game.board.snakes[0];
+
+    assert_eq!(snake.health, 100);
+    assert_eq!(snake.length, 4);
+    assert_eq!(
+        snake.body,
+        vec![coord(5, 6), coord(5, 5), coord(5, 4), coord(5, 4)]
+    );
+    assert_eq!(game.board.food, vec![coord(0, 0)]);
+}
+
+#[test]
+fn eating_on_the_last_health_point_survives() {
+    let mut game = game(
+        vec![
+            snake_with_health("a", &[(5, 5), (5, 4), (5, 3)], 1),
+            snake("b", &[(8, 8), (8, 7), (8, 6)]),
+        ],
+        vec![coord(5, 6)],
+        vec![],
+    );
+
+    let outcome = game.apply_moves(&[Some(Up), Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::None);
+    assert_eq!(body_of(&game, "a").len(), 4);
+}
+
+#[test]
+fn grown_tail_stays_in_place_for_one_turn() {
+    let mut game = game(
+        vec![snake("a", &[(5, 5), (5, 4), (5, 3)])],
+        vec![coord(5, 6)],
+        vec![],
+    );
+
+    game.apply_moves(&[Some(Up)]);
+    game.apply_moves(&[Some(Up)]);
+
+    assert_eq!(
+        body_of(&game, "a"),
+        vec![coord(5, 7), coord(5, 6), coord(5, 5), coord(5, 4)]
+    );
+}
+
+#[test]
+fn food_is_topped_up_to_the_minimum() {
+    let mut game = game(vec![snake("a", &[(5, 5), (5, 4), (5, 3)])], vec![], vec![]);
+    set_setting(&mut game, "minimumFood", json!(3));
+
+    spawn_food(&mut game);
+
+    assert_eq!(game.board.food.len(), 3);
+}
+
+#[test]
+fn food_spawns_by_chance_once_the_minimum_is_met() {
+    let mut game = game(
+        vec![snake("a", &[(5, 5), (5, 4), (5, 3)])],
+        vec![coord(0, 0)],
+        vec![],
+    );
+    set_setting(&mut game, "minimumFood", json!(1));
+
+    spawn_food(&mut game);
+    assert_eq!(game.board.food.len(), 1);
+
+    // Like the official engine, a chance of 100 still misses one time in a hundred
+    set_setting(&mut game, "foodSpawnChance", json!(100));
+
+    for _ in 0..20 {
+        spawn_food(&mut game);
+    }
+    assert!(game.board.food.len() > 15);
+}
+
+#[test]
+fn food_never_spawns_on_occupied_cells() {
+    // Only (8, 10) and (10, 10) are free: everything else is hazard, food, snake, or next to a snake's head
+    let hazards = (0..11)
+        .flat_map(|x| (0..11).map(move |y| coord(x, y)))
+        .filter(|coord| coord.x < 8 || coord.y < 8)
+        .collect();
+
+    let mut game = game(
+        vec![snake("a", &[(9, 9), (9, 8), (8, 8)])],
+        vec![coord(10, 8)],
+        hazards,
+    );
+    set_setting(&mut game, "minimumFood", json!(5));
+
+    spawn_food(&mut game);
+
+    let mut food = game.board.food.clone();
+    food.sort_by_key(|coord| (coord.x, coord.y));
+
+    assert_eq!(food, vec![coord(8, 10), coord(10, 8), coord(10, 10)]);
+    assert!(rules::unoccupied_coords(&game.board).is_empty());
+}
+
+#[test]
+fn official_spawns_stack_bodies_on_fixed_points_with_food_nearby() {
+    let spawn_coords = [
+        (1, 1),
+        (1, 9),
+        (9, 1),
+        (9, 9),
+        (1, 5),
+        (5, 1),
+        (5, 9),
+        (9, 5),
+    ]
+    .map(|(x, y)| coord(x, y));
+
+    for snakes_count in 1..=8 {
+        let game = GameWrapper::new(
+            11,
+            11,
+            snakes_count,
+            GameMode::Standard,
+            SnakeSpawn::Official,
+            snakes_count as u64,
+        );
+
+        assert_eq!(game.board.snakes.len(), snakes_count as usize);
+        assert_eq!(game.board.food.len(), snakes_count as usize + 1);
+        assert!(game.board.food.contains(&coord(5, 5)));
+
+        let mut heads = Vec::new();
+
+        for snake in &game.board.snakes {
+            assert!(spawn_coords.contains(&snake.head));
+            assert!(!heads.contains(&snake.head));
+            assert_eq!(snake.body, vec![snake.head; 3]);
+            assert_eq!(snake.length, 3);
+            assert_eq!(snake.health, 100);
+
+            heads.push(snake.head);
+        }
+
+        for food in game.board.food.iter().filter(|food| **food != coord(5, 5)) {
+            assert!(heads
+                .iter()
+                .any(|head| (head.x - food.x).abs() == 1 && (head.y - food.y).abs() == 1));
+        }
+    }
+}
+
+#[test]
+fn random_spawns_use_distinct_even_cells() {
+    for seed in 0..20 {
+        let game = GameWrapper::new(7, 11, 4, GameMode::Standard, SnakeSpawn::Random, seed);
+
+        let mut heads = Vec::new();
+
+        for snake in &game.board.snakes {
+            assert_eq!((snake.head.x + snake.head.y) % 2, 0);
+            assert!(!heads.contains(&snake.head));
+            assert!(!game.board.food.contains(&snake.head));
+            assert_eq!(snake.body, vec![snake.head; 3]);
+
+            heads.push(snake.head);
+        }
+
+        assert_eq!(game.board.food.len(), 4);
+    }
+}
+
+#[test]
+fn royale_hazards_close_in_on_shrink_turns() {
+    let mut game = game(vec![snake("a", &[(5, 5), (5, 4), (5, 3)])], vec![], vec![]);
+    game.game.ruleset = GameMode::Royale.ruleset();
+    set_setting(&mut game, "royale", json!({ "shrinkEveryNTurns": 3 }));
+
+    // The first shrink happens ahead of turn 3
+    game.turn = 1;
+    shrink_hazards(&mut game);
+    assert!(game.board.hazards.is_empty());
+
+    game.turn = 2;
+    shrink_hazards(&mut game);
+    assert_eq!(game.board.hazards.len(), 11);
+
+    let hazards: std::collections::HashSet<Coord> = game.board.hazards.iter().copied().collect();
+    let is_edge = |coord: &Coord| coord.x == 0 || coord.x == 10 || coord.y == 0 || coord.y == 10;
+    assert!(hazards.iter().all(is_edge));
+
+    // Hazards keep growing inward until only a single cell is safe
+    for turn in (5..).step_by(3).take(200) {
+        game.turn = turn;
+        shrink_hazards(&mut game);
+    }
+    assert_eq!(game.board.hazards.len(), 11 * 11 - 1);
+}
+
+#[test]
+fn royale_hazards_damage_snakes_standing_in_them() {
+    let mut game = game(
+        vec![
+            snake("a", &[(0, 5), (0, 4), (0, 3)]),
+            snake("b", &[(5, 5), (5, 4), (5, 3)]),
+        ],
+        vec![],
+        (0..11).map(|y| coord(0, y)).collect(),
+    );
+    game.game.ruleset = GameMode::Royale.ruleset();
+    set_setting(&mut game, "minimumFood", json!(0));
+    set_setting(&mut game, "foodSpawnChance", json!(0));
+
+    game.apply_moves(&[Some(Up), Some(Up)]);
+
+    assert_eq!(game.board.snakes[0].health, 100 - 1 - 14);
+    assert_eq!(game.board.snakes[1].health, 99);
+}
+
+#[test]
+fn constrictor_snakes_grow_every_turn_without_food() {
+    let mut game = game(
+        vec![
+            snake_with_health("a", &[(5, 5), (5, 4), (5, 3)], 50),
+            snake("b", &[(8, 8), (8, 7), (8, 6)]),
+        ],
+        vec![coord(0, 0)],
+        vec![],
+    );
+    game.game.ruleset = GameMode::Constrictor.ruleset();
+
+    game.apply_moves(&[Some(Up), Some(Up)]);
+    game.apply_moves(&[Some(Up), Some(Up)]);
+
+    let snake = &game.board.snakes[0];
+
+    assert!(game.board.food.is_empty());
+    assert_eq!(snake.health, 100);
+    assert_eq!(snake.length, 5);
+    assert_eq!(
+        snake.body,
+        vec![
+            coord(5, 7),
+            coord(5, 6),
+            coord(5, 5),
+            coord(5, 4),
+            coord(5, 4)
+        ]
+    );
+}
+
+#[test]
+fn constrictor_games_start_without_food() {
+    let game = GameWrapper::new(11, 11, 4, GameMode::Constrictor, SnakeSpawn::Official, 0);
+
+    assert!(game.board.food.is_empty());
+}
+
+#[test]
+fn squadmates_pass_through_each_other_but_not_opponents() {
+    let mut game = squad_game(vec![
+        squad_snake("a", "0", &[(5, 5), (4, 5), (3, 5)], 100),
+        squad_snake("b", "0", &[(6, 6), (6, 5), (6, 4)], 100),
+        squad_snake("c", "1", &[(7, 6), (8, 6), (9, 6)], 100),
+        squad_snake("d", "1", &[(1, 9), (1, 8), (1, 7)], 100),
+    ]);
+    set_setting(
+        &mut game,
+        "squad",
+        json!({ "allowBodyCollisions": true, "sharedElimination": false }),
+    );
+
+    let outcome = game.apply_moves(&[Some(Right), Some(Left), Some(Left), Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::None);
+    assert_eq!(
+        body_of(&game, "a"),
+        vec![coord(6, 5), coord(5, 5), coord(4, 5)]
+    );
+    assert_eq!(
+        causes_of(&game),
+        vec![(
+            "c".to_string(),
+            EliminatedCause::BodyCollision("b".to_string()),
+            1
+        )]
+    );
+}
+
+#[test]
+fn squadmates_collide_when_body_collisions_are_not_allowed() {
+    let mut game = squad_game(vec![
+        squad_snake("a", "0", &[(5, 5), (4, 5), (3, 5)], 100),
+        squad_snake("b", "0", &[(6, 6), (6, 5), (6, 4)], 100),
+    ]);
+    set_setting(&mut game, "squad", json!({ "allowBodyCollisions": false }));
+
+    let outcome = game.apply_moves(&[Some(Right), Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::Tie);
+    assert_eq!(
+        causes_of(&game),
+        vec![
+            (
+                "a".to_string(),
+                EliminatedCause::BodyCollision("b".to_string()),
+                1
+            ),
+            ("b".to_string(), EliminatedCause::SquadEliminated, 1),
+        ]
+    );
+}
+
+#[test]
+fn squads_are_eliminated_together_and_win_together() {
+    let mut game = squad_game(vec![
+        squad_snake("a", "0", &[(5, 5), (5, 4), (5, 3)], 100),
+        squad_snake("b", "0", &[(8, 8), (8, 7), (8, 6)], 100),
+        squad_snake("c", "1", &[(0, 5), (1, 5), (2, 5)], 100),
+        squad_snake("d", "1", &[(2, 8), (2, 7), (2, 6)], 100),
+    ]);
+
+    let outcome = game.apply_moves(&[Some(Up), Some(Up), Some(Left), Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::SquadWinner("0".to_string()));
+    assert_eq!(
+        causes_of(&game),
+        vec![
+            ("c".to_string(), EliminatedCause::OutOfBounds, 1),
+            ("d".to_string(), EliminatedCause::SquadEliminated, 1),
+        ]
+    );
+
+    let mut winners = game.squad_snake_ids("0");
+    winners.sort();
+
+    assert_eq!(winners, vec!["a".to_string(), "b".to_string()]);
+}
+
+#[test]
+fn squadmates_share_health_and_length() {
+    let mut game = squad_game(vec![
+        squad_snake("a", "0", &[(5, 5), (5, 4), (5, 3)], 40),
+        squad_snake("b", "0", &[(8, 8), (8, 7), (8, 6), (8, 5), (8, 4)], 90),
+        squad_snake("c", "1", &[(2, 2), (2, 1), (2, 0)], 10),
+    ]);
+
+    game.apply_moves(&[Some(Up), Some(Up), Some(Up)]);
+
+    let a = &game.board.snakes[0];
+
+    assert_eq!(a.health, 89);
+    assert_eq!(a.length, 5);
+    assert_eq!(
+        a.body,
+        vec![
+            coord(5, 6),
+            coord(5, 5),
+            coord(5, 4),
+            coord(5, 4),
+            coord(5, 4)
+        ]
+    );
+    assert_eq!(game.board.snakes[2].health, 9);
+}
+
+#[test]
+fn wrapped_heads_come_back_on_the_opposite_side() {
+    let mut game = game(
+        vec![
+            snake("a", &[(0, 5), (1, 5), (2, 5)]),
+            snake("b", &[(8, 10), (8, 9), (8, 8)]),
+        ],
+        vec![],
+        vec![],
+    );
+    game.game.ruleset = GameMode::Wrapped.ruleset();
+    set_setting(&mut game, "minimumFood", json!(0));
+    set_setting(&mut game, "foodSpawnChance", json!(0));
+
+    let outcome = game.apply_moves(&[Some(Left), Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::None);
+    assert_eq!(
+        body_of(&game, "a"),
+        vec![coord(10, 5), coord(0, 5), coord(1, 5)]
+    );
+    assert_eq!(
+        body_of(&game, "b"),
+        vec![coord(8, 0), coord(8, 10), coord(8, 9)]
+    );
+}
+
+#[test]
+fn wrapped_collisions_happen_across_edges() {
+    let mut game = game(
+        vec![
+            snake("a", &[(0, 5), (1, 5), (2, 5)]),
+            snake("b", &[(10, 6), (10, 5), (10, 4), (10, 3)]),
+        ],
+        vec![],
+        vec![],
+    );
+    game.game.ruleset = GameMode::Wrapped.ruleset();
+    set_setting(&mut game, "minimumFood", json!(0));
+
+    let outcome = game.apply_moves(&[Some(Left), Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::Winner("b".to_string()));
+}
+
+fn causes_of(game: &GameWrapper) -> Vec<(String, EliminatedCause, i32)> {
+    game.eliminations
+        .iter()
+        .map(|elimination| {
+            (
+                elimination.snake.id.clone(),
+                elimination.cause.clone(),
+                elimination.turn,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn eliminations_record_cause_and_turn() {
+    let mut game = game(
+        vec![
+            snake("oob", &[(0, 0), (1, 0), (2, 0)]),
+            snake_with_health("starved", &[(0, 10), (1, 10), (2, 10)], 1),
+            snake_with_health("hazard", &[(5, 0), (6, 0), (7, 0)], 10),
+            snake("self", &[(5, 5), (5, 4), (4, 4), (4, 5), (4, 6)]),
+            snake("body", &[(8, 9), (9, 9), (10, 9)]),
+            snake("long", &[(7, 9), (7, 10), (8, 10), (9, 10)]),
+            snake("short", &[(6, 8), (5, 8), (4, 8)]),
+        ],
+        vec![],
+        vec![coord(5, 1)],
+    );
+    game.turn = 4;
+
+    game.apply_moves(&[
+        Some(Left),
+        Some(Up),
+        Some(Up),
+        Some(Left),
+        Some(Left),
+        Some(Down),
+        Some(Right),
+    ]);
+
+    let mut causes = causes_of(&game);
+    causes.sort_by(|a, b| a.0.cmp(&b.0));
+
+    assert_eq!(
+        causes,
+        vec![
+            (
+                "body".to_string(),
+                EliminatedCause::BodyCollision("long".to_string()),
+                5
+            ),
+            ("hazard".to_string(), EliminatedCause::Hazard, 5),
+            ("oob".to_string(), EliminatedCause::OutOfBounds, 5),
+            ("self".to_string(), EliminatedCause::SelfCollision, 5),
+            (
+                "short".to_string(),
+                EliminatedCause::HeadToHead("long".to_string()),
+                5
+            ),
+            ("starved".to_string(), EliminatedCause::Starvation, 5),
+        ]
+    );
+    assert_eq!(game.board.snakes.len(), 1);
+}
+
+#[test]
+fn same_seed_replays_the_same_game() {
+    let play = |seed| {
+        let mut game = GameWrapper::new(11, 11, 4, GameMode::Royale, SnakeSpawn::Official, seed);
+        set_setting(&mut game, "royale", json!({ "shrinkEveryNTurns": 1 }));
+        set_setting(&mut game, "foodSpawnChance", json!(50));
+
+        for _ in 0..5 {
+            let moves = vec![Some(Up); game.board.snakes.len()];
+            game.apply_moves(&moves);
+        }
+
+        serde_json::to_string(&game.board).unwrap()
+    };
+
+    assert_eq!(play(7), play(7));
+    assert_ne!(play(7), play(8));
+}
+
+#[test]
+fn step_leaves_the_input_board_untouched_and_reports_events() {
+    let game = game(
+        vec![
+            snake_with_health("a", &[(5, 5), (5, 4), (5, 3)], 50),
+            snake("b", &[(0, 5), (1, 5), (2, 5)]),
+        ],
+        vec![coord(5, 6)],
+        vec![],
+    );
+    let before = serde_json::to_string(&game.board).unwrap();
+
+    let (board, events) = rules::step(
+        &game.board,
+        game.turn,
+        &ruleset(&game),
+        &[Up, Left],
+        &mut StdRng::seed_from_u64(0),
+    );
+
+    assert_eq!(serde_json::to_string(&game.board).unwrap(), before);
+    assert_eq!(board.snakes.len(), 1);
+    assert_eq!(board.snakes[0].health, 100);
+    assert!(matches!(
+        &events[..],
+        [
+            rules::Event::Ate { snake_id, food },
+            rules::Event::Eliminated(elimination),
+        ] if snake_id == "a" && *food == coord(5, 6) && elimination.cause == EliminatedCause::OutOfBounds
+    ));
+}
+
+#[test]
+fn make_move_matches_step_and_unmake_move_restores_the_board() {
+    for mode in [
+        GameMode::Standard,
+        GameMode::Constrictor,
+        GameMode::Wrapped,
+        GameMode::Squad,
+    ] {
+        let mut game = GameWrapper::new(7, 7, 4, mode, SnakeSpawn::Official, 3);
+        set_setting(&mut game, "minimumFood", json!(0));
+        set_setting(&mut game, "foodSpawnChance", json!(0));
+        game.board.hazards = vec![coord(3, 2), coord(3, 2), coord(2, 3)];
+
+        let ruleset = ruleset(&game);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut history = Vec::new();
+
+        while !game.board.snakes.is_empty() && history.len() < 30 {
+            let moves: Vec<_> = game
+                .board
+                .snakes
+                .iter()
+                .map(|_| *rules::Direction::ALL.choose(&mut rng).unwrap())
+                .collect();
+
+            let before = serde_json::to_string(&game.board).unwrap();
+            let (expected, _) = rules::step(&game.board, 0, &ruleset, &moves, &mut rng);
+
+            let unmake = make_move(&mut game.board, &ruleset, &moves);
+
+            assert_eq!(
+                serde_json::to_string(&game.board).unwrap(),
+                serde_json::to_string(&expected).unwrap(),
+                "{:?} turn {}",
+                mode,
+                history.len()
+            );
+
+            history.push((before, unmake));
+        }
+
+        while let Some((before, unmake)) = history.pop() {
+            unmake_move(&mut game.board, unmake);
+
+            assert_eq!(serde_json::to_string(&game.board).unwrap(), before);
+        }
+    }
+}
+
+#[test]
+fn unmake_move_restores_every_kind_of_elimination() {
+    let mut game = game(
+        vec![
+            snake("oob", &[(0, 0), (1, 0), (2, 0)]),
+            snake_with_health("starved", &[(0, 10), (1, 10), (2, 10)], 1),
+            snake_with_health("hazard", &[(5, 0), (6, 0), (7, 0)], 10),
+            snake("self", &[(5, 5), (5, 4), (4, 4), (4, 5), (4, 6)]),
+            snake("body", &[(8, 9), (9, 9), (10, 9)]),
+            snake("long", &[(7, 9), (7, 10), (8, 10), (9, 10)]),
+            snake("short", &[(6, 8), (5, 8), (4, 8)]),
+            snake_with_health("eater", &[(2, 2), (2, 1), (2, 1)], 30),
+        ],
+        vec![coord(0, 5), coord(2, 3), coord(9, 5)],
+        vec![coord(5, 1)],
+    );
+    let ruleset = ruleset(&game);
+    let before = serde_json::to_string(&game.board).unwrap();
+
+    let unmake = make_move(
+        &mut game.board,
+        &ruleset,
+        &[Left, Up, Up, Left, Left, Down, Right, Up],
+    );
+
+    assert_eq!(game.board.snakes.len(), 2);
+    assert_eq!(game.board.food, vec![coord(0, 5), coord(9, 5)]);
+    assert_eq!(
+        body_of(&game, "eater"),
+        vec![coord(2, 3), coord(2, 2), coord(2, 1), coord(2, 1)]
+    );
+
+    unmake_move(&mut game.board, unmake);
+
+    assert_eq!(serde_json::to_string(&game.board).unwrap(), before);
+}
+
+#[test]
+fn fast_board_steps_like_the_rules() {
+    for mode in [
+        GameMode::Standard,
+        GameMode::Royale,
+        GameMode::Constrictor,
+        GameMode::Wrapped,
+        GameMode::Squad,
+    ] {
+        for seed in 0..20 {
+            let mut game = GameWrapper::new(11, 11, 4, mode, SnakeSpawn::Official, seed);
+            set_setting(&mut game, "royale", json!({ "shrinkEveryNTurns": 3 }));
+
+            let ruleset = ruleset(&game);
+            let starting_board = game.board.clone();
+            let mut fast_board = FastBoard::from_board(&game.board);
+            let mut wrapped_game = game.clone();
+            wrapped_game.rng = StdRng::seed_from_u64(seed);
+            let mut eliminated = Vec::new();
+            let mut move_rng = StdRng::seed_from_u64(seed);
+            let mut rules_rng = StdRng::seed_from_u64(seed);
+            let mut fast_rng = StdRng::seed_from_u64(seed);
+
+            for turn in 0..100 {
+                if game.board.snakes.is_empty() {
+                    break;
+                }
+
+                let moves: Vec<_> = game
+                    .board
+                    .snakes
+                    .iter()
+                    .map(|_| *rules::Direction::ALL.choose(&mut move_rng).unwrap())
+                    .collect();
+
+                let (board, events) =
+                    rules::step(&game.board, turn, &ruleset, &moves, &mut rules_rng);
+                eliminated.clear();
+                fast_board.step(turn, &ruleset, &moves, &mut fast_rng, &mut eliminated);
+                wrapped_game.apply_moves(&moves.iter().copied().map(Some).collect::<Vec<_>>());
+                game.board = board;
+
+                let expected = &game.board;
+                let actual = fast_board.to_board(&starting_board);
+
+                assert_eq!(actual.food, expected.food, "{:?} {}", mode, seed);
+                assert_eq!(actual.hazards, expected.hazards);
+                assert_eq!(
+                    serde_json::to_string(&actual.snakes).unwrap(),
+                    serde_json::to_string(&expected.snakes).unwrap()
+                );
+
+                let expected_eliminations: Vec<_> = events
+                    .iter()
+                    .filter_map(|event| match event {
+                        rules::Event::Eliminated(elimination) => Some(elimination),
+                        _ => None,
+                    })
+                    .map(|elimination| {
+                        (
+                            serde_json::to_string(&elimination.snake).unwrap(),
+                            elimination.cause.clone(),
+                            elimination.turn,
+                        )
+                    })
+                    .collect();
+                let actual_eliminations: Vec<_> = eliminated
+                    .iter()
+                    .map(|elimination| elimination.to_elimination(&starting_board))
+                    .map(|elimination| {
+                        (
+                            serde_json::to_string(&elimination.snake).unwrap(),
+                            elimination.cause,
+                            elimination.turn,
+                        )
+                    })
+                    .collect();
+
+                assert_eq!(actual_eliminations, expected_eliminations);
+
+                // The game steps the same board, reusing its `FastBoard` from turn to turn
+
+                assert_eq!(
+                    serde_json::to_string(&wrapped_game.board).unwrap(),
+                    serde_json::to_string(expected).unwrap()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn turn_limit_ends_the_game_with_a_draw() {
+    let mut game = game(
+        vec![
+            snake("a", &[(5, 5), (5, 4), (5, 3), (5, 2)]),
+            snake("b", &[(8, 8), (8, 7), (8, 6)]),
+        ],
+        vec![],
+        vec![],
+    );
+    game.max_turns = 2;
+
+    assert_eq!(
+        game.apply_moves(&[Some(Up), Some(Up)]),
+        GameStepOutcome::None
+    );
+    assert_eq!(
+        game.apply_moves(&[Some(Up), Some(Left)]),
+        GameStepOutcome::TurnLimit(None)
+    );
+}
+
+#[test]
+fn turn_limit_ranks_by_length_then_health() {
+    let play = |a: Battlesnake, b: Battlesnake| {
+        let mut game = game(vec![a, b], vec![], vec![]);
+        game.max_turns = 1;
+        game.adjudication = Adjudication::Rank;
+
+        game.apply_moves(&[Some(Up), Some(Up)])
+    };
+
+    assert_eq!(
+        play(
+            snake_with_health("a", &[(5, 5), (5, 4), (5, 3), (5, 2)], 10),
+            snake("b", &[(8, 8), (8, 7), (8, 6)]),
+        ),
+        GameStepOutcome::TurnLimit(Some("a".to_string()))
+    );
+    assert_eq!(
+        play(
+            snake_with_health("a", &[(5, 5), (5, 4), (5, 3)], 10),
+            snake("b", &[(8, 8), (8, 7), (8, 6)]),
+        ),
+        GameStepOutcome::TurnLimit(Some("b".to_string()))
+    );
+    assert_eq!(
+        play(
+            snake("a", &[(5, 5), (5, 4), (5, 3)]),
+            snake("b", &[(8, 8), (8, 7), (8, 6)]),
+        ),
+        GameStepOutcome::TurnLimit(None)
+    );
+}
+
+#[test]
+fn snakes_without_a_move_repeat_their_last_one() {
+    let mut game = game(
+        vec![
+            snake("a", &[(5, 5), (4, 5), (3, 5)]),
+            snake("b", &[(8, 8), (8, 8), (8, 8)]),
+        ],
+        vec![],
+        vec![],
+    );
+
+    let outcome = game.apply_moves(&[None, None]);
+
+    assert_eq!(outcome, GameStepOutcome::None);
+    assert_eq!(
+        body_of(&game, "a"),
+        vec![coord(6, 5), coord(5, 5), coord(4, 5)]
+    );
+    assert_eq!(
+        body_of(&game, "b"),
+        vec![coord(8, 9), coord(8, 8), coord(8, 8)]
+    );
+}
+
+#[test]
+fn snakes_after_the_last_move_repeat_their_last_one() {
+    let mut game = game(
+        vec![
+            snake("a", &[(5, 5), (4, 5), (3, 5)]),
+            snake("b", &[(8, 8), (8, 7), (8, 6)]),
+        ],
+        vec![],
+        vec![],
+    );
+
+    let outcome = game.apply_moves(&[Some(Up)]);
+
+    assert_eq!(outcome, GameStepOutcome::None);
+    assert_eq!(
+        body_of(&game, "a"),
+        vec![coord(5, 6), coord(5, 5), coord(4, 5)]
+    );
+    assert_eq!(
+        body_of(&game, "b"),
+        vec![coord(8, 9), coord(8, 8), coord(8, 7)]
+    );
+}
+
+#[test]
+fn last_move_follows_wrapped_edges() {
+    let a = snake("a", &[(0, 5), (10, 5), (9, 5)]);
+    let geometry = Geometry::new(11, 11).with_wrapping(true);
+
+    assert_eq!(rules::last_move(&a, &geometry), Right);
+}
+
+/// Always moves the same way, counting the hooks it was called with
+#[derive(Default)]
+struct ScriptedAgent {
+    direction: Option<rules::Direction>,
+    log: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+}
+
+impl Agent for ScriptedAgent {
+    fn start(&mut self, _game: &Game, _turn: &i32, _board: &Board, me: &Battlesnake) {
+        self.log.borrow_mut().push(format!("start {}", me.id));
+    }
+
+    fn get_move(
+        &mut self,
+        _game: &Game,
+        _turn: &i32,
+        _board: &Board,
+        _me: &Battlesnake,
+    ) -> Option<rules::Direction> {
+        self.direction
+    }
+
+    fn end(&mut self, _game: &Game, _turn: &i32, _board: &Board, me: &Battlesnake) {
+        self.log.borrow_mut().push(format!("end {}", me.id));
+    }
+}
+
+/// Moves up, but only after `delay`
+struct SlowAgent {
+    delay: std::time::Duration,
+}
+
+impl Agent for SlowAgent {
+    fn get_move(
+        &mut self,
+        _game: &Game,
+        _turn: &i32,
+        _board: &Board,
+        _me: &Battlesnake,
+    ) -> Option<rules::Direction> {
+        std::thread::sleep(self.delay);
+
+        Some(Up)
+    }
+}
+
+/// `a` answers well after the game's timeout, and `b` at once
+fn slow_game(enforce_timeout: bool) -> (GameWrapper, HashMap<String, Box<dyn Agent>>) {
+    let mut game = game(
+        vec![
+            snake("a", &[(5, 5), (4, 5), (3, 5)]),
+            snake("b", &[(8, 8), (8, 7), (8, 6)]),
+        ],
+        vec![],
+        vec![],
+    );
+    game.game.timeout = 5;
+    game.enforce_timeout = enforce_timeout;
+
+    let mut agents: HashMap<String, Box<dyn Agent>> = HashMap::new();
+    agents.insert(
+        "a".to_string(),
+        Box::new(SlowAgent {
+            delay: std::time::Duration::from_millis(20),
+        }),
+    );
+    agents.insert(
+        "b".to_string(),
+        Box::new(ScriptedAgent {
+            direction: Some(Up),
+            ..Default::default()
+        }),
+    );
+
+    (game, agents)
+}
+
+#[test]
+fn snakes_over_the_timeout_repeat_their_last_move() {
+    let (mut game, mut agents) = slow_game(true);
+
+    game.turn_step(&mut agents);
+
+    assert_eq!(
+        body_of(&game, "a"),
+        vec![coord(6, 5), coord(5, 5), coord(4, 5)]
+    );
+    assert_eq!(
+        body_of(&game, "b"),
+        vec![coord(8, 9), coord(8, 8), coord(8, 7)]
+    );
+
+    let latency: u64 = game.board.snakes[0].latency.parse().unwrap();
+    assert!(latency >= 20);
+}
+
+#[test]
+fn timeouts_are_ignored_unless_enforced() {
+    let (mut game, mut agents) = slow_game(false);
+
+    game.turn_step(&mut agents);
+
+    assert_eq!(
+        body_of(&game, "a"),
+        vec![coord(5, 6), coord(5, 5), coord(4, 5)]
+    );
+
+    let latency: u64 = game.board.snakes[0].latency.parse().unwrap();
+    assert!(latency >= 20);
+}
+
+#[tokio::test]
+async fn agents_are_started_moved_and_ended() {
+    let mut game = game(
+        vec![
+            snake("a", &[(5, 5), (5, 4), (5, 3)]),
+            snake("b", &[(1, 5), (2, 5), (3, 5)]),
+        ],
+        vec![],
+        vec![],
+    );
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+
+    let mut agents: HashMap<String, Box<dyn Agent>> = HashMap::new();
+    agents.insert(
+        "a".to_string(),
+        Box::new(ScriptedAgent {
+            direction: Some(Up),
+            log: log.clone(),
+        }),
+    );
+    agents.insert(
+        "b".to_string(),
+        Box::new(ScriptedAgent {
+            direction: Some(Left),
+            log: log.clone(),
+        }),
+    );
+
+    let outcome = game.play_for_outcome(&mut agents).await;
+
+    assert_eq!(outcome, GameStepOutcome::Winner("a".to_string()));
+    assert_eq!(game.turn, 2);
+    assert_eq!(
+        body_of(&game, "a"),
+        vec![coord(5, 7), coord(5, 6), coord(5, 5)]
+    );
+    assert_eq!(*log.borrow(), vec!["start a", "start b", "end a", "end b"]);
+}
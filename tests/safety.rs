@@ -0,0 +1,129 @@
+mod common;
+
+use ml_battle_snake::{
+    ml_snake::safety::{MoveMask, MoveSafety},
+    simulation::rules::{
+        Direction::{self, Down, Left, Right, Up},
+        GameMode,
+    },
+    Battlesnake, Game,
+};
+
+use common::{board, game, snake};
+
+fn mask(mode: GameMode, snakes: Vec<Battlesnake>) -> MoveMask {
+    let game = Game {
+        ruleset: mode.ruleset(),
+        ..game()
+    };
+    let board = board(snakes, Vec::new());
+
+    MoveMask::new(&game, &board, &board.snakes[0])
+}
+
+fn safeties(mask: &MoveMask) -> Vec<(Direction, MoveSafety)> {
+    [Up, Down, Left, Right]
+        .into_iter()
+        .map(|direction| (direction, mask.safety(direction)))
+        .collect()
+}
+
+#[test]
+fn walls_and_the_neck_are_illegal() {
+    let mask = mask(
+        GameMode::Standard,
+        vec![snake("a", &[(0, 10), (1, 10), (2, 10)])],
+    );
+
+    assert_eq!(
+        safeties(&mask),
+        vec![
+            (Up, MoveSafety::Illegal),
+            (Down, MoveSafety::Safe),
+            (Left, MoveSafety::Illegal),
+            (Right, MoveSafety::Illegal),
+        ]
+    );
+}
+
+#[test]
+fn wrapped_walls_are_legal() {
+    let mask = mask(
+        GameMode::Wrapped,
+        vec![snake("a", &[(0, 10), (1, 10), (2, 10)])],
+    );
+
+    assert_eq!(mask.safety(Up), MoveSafety::Safe);
+    assert_eq!(mask.safety(Left), MoveSafety::Safe);
+}
+
+#[test]
+fn tails_that_move_away_are_safe_but_stacked_tails_are_not() {
+    let mask = mask(
+        GameMode::Standard,
+        vec![
+            snake("a", &[(5, 5), (5, 4), (4, 4), (4, 5)]),
+            snake("b", &[(6, 6), (7, 6), (7, 5), (6, 5), (6, 5)]),
+        ],
+    );
+
+    assert_eq!(mask.safety(Left), MoveSafety::Safe);
+    assert_eq!(mask.safety(Right), MoveSafety::Illegal);
+}
+
+#[test]
+fn moves_next_to_longer_heads_are_risky() {
+    let mask = mask(
+        GameMode::Standard,
+        vec![
+            snake("a", &[(5, 5), (5, 4), (5, 3)]),
+            snake("b", &[(5, 7), (5, 8), (5, 9)]),
+            snake("c", &[(3, 5), (2, 5)]),
+        ],
+    );
+
+    assert_eq!(mask.safety(Up), MoveSafety::RiskyHeadToHead);
+    assert_eq!(mask.safety(Left), MoveSafety::Safe);
+    assert_eq!(mask.allowed_moves(), vec![Left, Right]);
+}
+
+#[test]
+fn choose_prefers_the_best_scoring_safest_move() {
+    let mask = mask(
+        GameMode::Standard,
+        vec![
+            snake("a", &[(5, 5), (5, 4), (5, 3)]),
+            snake("b", &[(5, 7), (5, 8), (5, 9), (5, 10)]),
+        ],
+    );
+    let scores = [(Up, 0.9), (Down, 0.8), (Left, 0.1), (Right, 0.3)];
+
+    assert_eq!(mask.choose(&scores), Some(Right));
+}
+
+#[test]
+fn choose_falls_back_to_risky_then_illegal_moves() {
+    let scores = [(Up, 0.9), (Down, 0.8), (Left, 0.1), (Right, 0.3)];
+
+    let trapped = mask(
+        GameMode::Standard,
+        vec![
+            snake("a", &[(0, 0), (0, 1), (0, 2)]),
+            snake("b", &[(2, 0), (3, 0), (4, 0), (5, 0)]),
+        ],
+    );
+
+    assert_eq!(trapped.best_safety(), MoveSafety::RiskyHeadToHead);
+    assert_eq!(trapped.choose(&scores), Some(Right));
+
+    let boxed_in = mask(
+        GameMode::Standard,
+        vec![
+            snake("a", &[(0, 0), (0, 1), (0, 2)]),
+            snake("b", &[(2, 1), (1, 1), (1, 0), (2, 0), (3, 0)]),
+        ],
+    );
+
+    assert_eq!(boxed_in.best_safety(), MoveSafety::Illegal);
+    assert_eq!(boxed_in.choose(&scores), Some(Up));
+}
@@ -0,0 +1,404 @@
+mod common;
+
+use std::time::Duration;
+
+use ml_battle_snake::{
+    constants::WIN_SCORE,
+    geometry::Geometry,
+    ml_snake::{
+        agent::Agent,
+        mcts::MctsAgent,
+        minimax::AlphaBetaAgent,
+        multiplayer::{Backup, MultiplayerAgent},
+        safety::{MoveMask, MoveSafety},
+        search::{time_budget, Evaluator, HeuristicEvaluator, NeuralNetworkEvaluator, PolicyValue},
+        space::SpaceAgent,
+    },
+    neural_network::{NeuralNetwork, NeuralNetworkManager},
+    simulation::rules::Direction::{self, Down, Left, Right, Up},
+    utils::build_neural_network,
+    Battlesnake, Board, Game,
+};
+
+use common::{board, coord, game, snake, snake_with_health};
+
+/// A game with time enough for any search here to reach its depth or iteration limit, so results
+/// don't depend on how fast the machine is
+fn search_game() -> Game {
+    Game {
+        timeout: 60_000,
+        ..game()
+    }
+}
+
+/// The shorter snake `b` is heading into the corner, and cutting it off wins within two turns
+fn cornered_board() -> Board {
+    board(
+        vec![
+            snake("a", &[(2, 1), (3, 1), (4, 1), (5, 1)]),
+            snake("b", &[(0, 1), (0, 2), (0, 3)]),
+        ],
+        Vec::new(),
+    )
+}
+
+#[test]
+fn alpha_beta_finds_a_forced_win() {
+    let board = cornered_board();
+
+    let mut agent = AlphaBetaAgent::new(HeuristicEvaluator::default());
+    agent.limits.max_depth = 4;
+
+    let Some(result) = agent.search(&search_game(), &board, &board.snakes[0]) else {
+        panic!("no search result");
+    };
+
+    assert_eq!(result.best_move, Left);
+    assert_eq!(result.score, WIN_SCORE - 2.);
+    assert_eq!(result.depth, 2);
+}
+
+#[test]
+fn alpha_beta_searches_to_its_max_depth() {
+    let board = board(
+        vec![
+            snake("a", &[(2, 2), (2, 1), (2, 0)]),
+            snake("b", &[(8, 8), (8, 9), (8, 10)]),
+        ],
+        Vec::new(),
+    );
+
+    let mut agent = AlphaBetaAgent::new(HeuristicEvaluator::default());
+    agent.limits.max_depth = 3;
+
+    let Some(result) = agent.search(&search_game(), &board, &board.snakes[0]) else {
+        panic!("no search result");
+    };
+
+    assert_eq!(result.depth, 3);
+    assert!(result.score.abs() < WIN_SCORE / 2.);
+
+    let move_mask = MoveMask::new(&game(), &board, &board.snakes[0]);
+    assert_eq!(move_mask.safety(result.best_move), MoveSafety::Safe);
+}
+
+#[test]
+fn alpha_beta_leaves_games_with_more_snakes_to_the_fallback() {
+    let board = board(
+        vec![
+            snake("a", &[(2, 2), (2, 1), (2, 0)]),
+            snake("b", &[(8, 8), (8, 9), (8, 10)]),
+            snake("c", &[(8, 2), (8, 1), (8, 0)]),
+        ],
+        Vec::new(),
+    );
+
+    let mut agent = AlphaBetaAgent::new(HeuristicEvaluator::default());
+
+    assert_eq!(agent.search(&search_game(), &board, &board.snakes[0]), None);
+    assert!(agent
+        .get_move(&search_game(), &0, &board, &board.snakes[0])
+        .is_some());
+}
+
+#[test]
+fn alpha_beta_can_evaluate_with_a_network() {
+    let board = cornered_board();
+
+    let mut agent = AlphaBetaAgent::new(NeuralNetworkEvaluator::new(zero_network()));
+    agent.limits.max_depth = 1;
+
+    let chosen_move = agent.get_move(&search_game(), &0, &board, &board.snakes[0]);
+
+    let move_mask = MoveMask::new(&game(), &board, &board.snakes[0]);
+    assert!(chosen_move.is_some_and(|direction| move_mask.safety(direction) != MoveSafety::Illegal));
+}
+
+#[test]
+fn heuristic_prefers_being_longer() {
+    let board = board(
+        vec![
+            snake("a", &[(2, 2), (2, 1), (2, 0), (1, 0)]),
+            snake("b", &[(8, 8), (8, 9), (8, 10)]),
+        ],
+        Vec::new(),
+    );
+
+    let mut evaluator = HeuristicEvaluator::default();
+
+    assert!(
+        evaluator.evaluate(&game(), &board, &board.snakes[0])
+            > evaluator.evaluate(&game(), &board, &board.snakes[1])
+    );
+}
+
+#[test]
+fn mcts_finds_a_forced_win() {
+    let board = cornered_board();
+    let game = search_game();
+
+    let mut agent = MctsAgent::new(None);
+    agent.max_iterations = Some(2000);
+
+    let Some(result) = agent.search(&game, &board, &board.snakes[0]) else {
+        panic!("no search result");
+    };
+
+    assert_eq!(result.best_move, Left);
+    assert_eq!(result.iterations, 2000);
+
+    // Every iteration passes through one of our moves at the root
+
+    let visits: u32 = result.visits.iter().map(|(_, visits)| visits).sum();
+    assert_eq!(visits, result.iterations);
+}
+
+fn zero_network() -> NeuralNetwork {
+    let mut network_manager = NeuralNetworkManager::new();
+    let mut network = NeuralNetwork::new(&mut network_manager);
+    build_neural_network(&mut network, &Geometry::new(11, 11));
+
+    network
+}
+
+/// `a` in the open with Up, Left and Right to choose from, and food to its right when `food` is set
+fn open_board(food: bool) -> Board {
+    board(
+        vec![
+            snake("a", &[(5, 5), (5, 4), (5, 3)]),
+            snake("b", &[(1, 9), (1, 10), (2, 10)]),
+        ],
+        if food { vec![coord(6, 5)] } else { Vec::new() },
+    )
+}
+
+fn mcts_best_move(network: impl PolicyValue + Send + 'static, board: &Board) -> Direction {
+    let game = search_game();
+
+    let mut agent = MctsAgent::new(None);
+    agent.network = Some(Box::new(network));
+    agent.max_iterations = Some(150);
+
+    let Some(result) = agent.search(&game, board, &board.snakes[0]) else {
+        panic!("no search result");
+    };
+
+    result.best_move
+}
+
+#[test]
+fn mcts_can_use_network_priors() {
+    let board = cornered_board();
+
+    let mut agent = MctsAgent::new(Some(zero_network()));
+    agent.max_iterations = Some(50);
+
+    let chosen_move = agent.get_move(&search_game(), &0, &board, &board.snakes[0]);
+
+    let move_mask = MoveMask::new(&game(), &board, &board.snakes[0]);
+    assert!(chosen_move.is_some_and(|direction| move_mask.safety(direction) != MoveSafety::Illegal));
+}
+
+#[test]
+fn mcts_stops_when_its_time_budget_runs_out() {
+    let board = cornered_board();
+    let game = Game {
+        timeout: 150,
+        ..game()
+    };
+
+    assert_eq!(
+        time_budget(&game, Duration::from_millis(100)),
+        Duration::from_millis(50)
+    );
+    assert_eq!(
+        time_budget(&game, Duration::from_millis(200)),
+        Duration::ZERO
+    );
+
+    // Leaving the whole timeout unused has no time for a single iteration, so the fallback answers
+
+    let mut agent = MctsAgent::new(None);
+    agent.latency_margin = Duration::from_millis(150);
+
+    assert_eq!(agent.search(&game, &board, &board.snakes[0]), None);
+    assert_eq!(
+        agent.get_move(&game, &0, &board, &board.snakes[0]),
+        SpaceAgent.get_move(&game, &0, &board, &board.snakes[0])
+    );
+}
+
+#[test]
+fn mcts_visits_the_moves_network_priors_favour() {
+    let board = open_board(false);
+
+    assert_ne!(mcts_best_move(zero_network(), &board), Left);
+
+    // Every hidden activation is the bias, so weighting the left output by them favours Left anywhere
+
+    let mut network = zero_network();
+    let output_layer = network.weight_layers.len() - 1;
+
+    for weight in &mut network.weight_layers[output_layer][2] {
+        *weight = 10.;
+    }
+
+    assert_eq!(mcts_best_move(network, &board), Left);
+}
+
+/// Scores every move the same and values a board at twice our length less 7, which only turns
+/// positive once we've eaten
+struct LengthValue;
+
+impl PolicyValue for LengthValue {
+    fn policy_value(
+        &mut self,
+        _game: &Game,
+        _board: &Board,
+        me: &Battlesnake,
+    ) -> (Vec<(Direction, f32)>, f32) {
+        let move_scores = Direction::ALL
+            .into_iter()
+            .map(|direction| (direction, 0.))
+            .collect();
+
+        (move_scores, 2. * me.length as f32 - 7.)
+    }
+}
+
+#[test]
+fn mcts_visits_the_moves_values_favour() {
+    let board = open_board(true);
+
+    assert_ne!(mcts_best_move(zero_network(), &board), Right);
+    assert_eq!(mcts_best_move(LengthValue, &board), Right);
+}
+
+/// `a` is shorter than `b`, whose head is two cells above its own, with `c` out of the way
+fn three_snake_board() -> Board {
+    board(
+        vec![
+            snake("a", &[(5, 5), (5, 4), (5, 3)]),
+            snake("b", &[(5, 7), (5, 8), (5, 9), (6, 9), (7, 9)]),
+            snake("c", &[(1, 1), (1, 0), (2, 0)]),
+        ],
+        Vec::new(),
+    )
+}
+
+#[test]
+fn paranoid_search_avoids_moves_any_opponent_can_punish() {
+    let board = three_snake_board();
+
+    let mut agent = MultiplayerAgent::new(HeuristicEvaluator::default(), Backup::Paranoid);
+    agent.limits.max_depth = 2;
+
+    let Some(result) = agent.search(&search_game(), &board, &board.snakes[0]) else {
+        panic!("no search result");
+    };
+
+    assert_ne!(result.best_move, Up);
+    assert!(result.score > -WIN_SCORE / 2.);
+    assert_eq!(result.depth, 2);
+}
+
+#[test]
+fn max_n_search_finds_a_forced_win() {
+    let board = cornered_board();
+
+    let mut agent = MultiplayerAgent::new(HeuristicEvaluator::default(), Backup::MaxN);
+    agent.limits.max_depth = 4;
+
+    let Some(result) = agent.search(&search_game(), &board, &board.snakes[0]) else {
+        panic!("no search result");
+    };
+
+    assert_eq!(result.best_move, Left);
+    assert_eq!(result.score, WIN_SCORE - 2.);
+}
+
+#[test]
+fn max_n_search_plays_three_snakes() {
+    let board = three_snake_board();
+
+    let mut agent = MultiplayerAgent::new(HeuristicEvaluator::default(), Backup::MaxN);
+    agent.limits.max_depth = 2;
+
+    let chosen_move = agent.get_move(&search_game(), &0, &board, &board.snakes[0]);
+
+    let move_mask = MoveMask::new(&game(), &board, &board.snakes[0]);
+    assert!(chosen_move.is_some_and(|direction| move_mask.safety(direction) != MoveSafety::Illegal));
+}
+
+/// `a` in the top-left corner can only move right, and `b` of the same length only left, into
+/// the same cell, eliminating both
+#[test]
+fn both_backups_score_a_forced_draw_as_even() {
+    let board = board(
+        vec![
+            snake("a", &[(0, 10), (0, 9), (0, 8), (0, 7), (0, 6)]),
+            snake("b", &[(2, 10), (2, 9), (3, 9), (3, 10), (4, 10)]),
+        ],
+        Vec::new(),
+    );
+
+    for backup in [Backup::Paranoid, Backup::MaxN] {
+        let mut agent = MultiplayerAgent::new(HeuristicEvaluator::default(), backup);
+        agent.limits.max_depth = 2;
+
+        let Some(result) = agent.search(&search_game(), &board, &board.snakes[0]) else {
+            panic!("no search result");
+        };
+
+        assert_eq!(result.best_move, Right, "{:?}", backup);
+        assert_eq!(result.score, 0., "{:?}", backup);
+    }
+}
+
+#[test]
+fn max_n_search_puts_off_losses() {
+    // Up into the hazard starves `a` right away, while down leads it into a dead end between the
+    // wall and `b` to be eliminated on the third turn
+
+    let board = Board {
+        hazards: vec![coord(0, 3)],
+        ..board(
+            vec![
+                snake_with_health("a", &[(0, 2), (1, 2), (2, 2)], 15),
+                snake(
+                    "b",
+                    &[
+                        (2, 1),
+                        (1, 1),
+                        (1, 0),
+                        (2, 0),
+                        (3, 0),
+                        (4, 0),
+                        (5, 0),
+                        (6, 0),
+                    ],
+                ),
+                snake("c", &[(8, 8), (8, 9), (8, 10)]),
+            ],
+            Vec::new(),
+        )
+    };
+
+    let mut agent = MultiplayerAgent::new(HeuristicEvaluator::default(), Backup::MaxN);
+    agent.limits.max_depth = 3;
+
+    let Some(result) = agent.search(&search_game(), &board, &board.snakes[0]) else {
+        panic!("no search result");
+    };
+
+    assert_eq!(result.best_move, Down);
+    assert_eq!(result.score, -WIN_SCORE + 3.);
+    assert_eq!(result.depth, 3);
+}
+
+#[test]
+fn backup_names_round_trip() {
+    for backup in [Backup::Paranoid, Backup::MaxN] {
+        assert_eq!(Backup::from_name(backup.name()), Some(backup));
+    }
+}
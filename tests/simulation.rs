@@ -0,0 +1,59 @@
+use ml_battle_snake::{
+    geometry::Geometry,
+    neural_network::{NeuralNetwork, NeuralNetworkManager},
+    simulation::{game::GameStepOutcome, rules::GameMode, simulation::Simulation},
+    utils::build_neural_network,
+};
+
+const GAMES_COUNT: u32 = 4;
+const SNAKES_COUNT: u32 = 2;
+
+#[tokio::test]
+async fn only_the_winners_networks_survive_a_round() {
+    // Constrictor games on a small board fill up fast, so most end with a winner
+    let mut simulation = Simulation::new(1);
+    simulation.mode = GameMode::Constrictor;
+
+    let mut network_manager = NeuralNetworkManager::new();
+    let mut networks: Vec<NeuralNetwork> = (0..GAMES_COUNT * SNAKES_COUNT)
+        .map(|_| {
+            let mut network = NeuralNetwork::new(&mut network_manager);
+            build_neural_network(&mut network, &Geometry::new(7, 7));
+            network.mutate(&mut simulation.rng);
+
+            network
+        })
+        .collect();
+    let network_ids: Vec<String> = networks.iter().map(|network| network.id.clone()).collect();
+
+    let game_outcomes = simulation
+        .run_games(GAMES_COUNT, 7, 7, SNAKES_COUNT, 0, &mut networks)
+        .await;
+
+    // Game i is played by networks i * SNAKES_COUNT onwards, as snakes "0", "1" and so on
+    let winner_ids: Vec<String> = game_outcomes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, outcome)| match outcome {
+            GameStepOutcome::Winner(snake_id) => {
+                let relative_id: usize = snake_id.parse().unwrap();
+
+                Some(network_ids[i * SNAKES_COUNT as usize + relative_id].clone())
+            }
+            _ => None,
+        })
+        .collect();
+
+    // The run has a winner that isn't snake "0" of the first game, so each game's networks are told apart
+    assert!(game_outcomes
+        .iter()
+        .skip(1)
+        .any(|outcome| *outcome == GameStepOutcome::Winner("1".to_string())));
+    assert_eq!(
+        networks
+            .iter()
+            .map(|network| network.id.clone())
+            .collect::<Vec<String>>(),
+        winner_ids
+    );
+}
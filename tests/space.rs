@@ -0,0 +1,59 @@
+mod common;
+
+use ml_battle_snake::{
+    ml_snake::{
+        agent::Agent,
+        space::{SpaceAgent, SpaceAnalysis},
+    },
+    simulation::rules::Direction::{Down, Left, Up},
+};
+
+use common::{board, coord, game, pocket_board, snake};
+
+#[test]
+fn pockets_too_small_to_fit_in_are_traps() {
+    let board = pocket_board(vec![]);
+    let space_analysis = SpaceAnalysis::new(&game(), &board, &board.snakes[0]);
+
+    let up = space_analysis.get(Up);
+
+    assert_eq!(up.area, 1);
+    assert!(!up.reaches_tail);
+    assert!(up.is_trapped());
+
+    // Everything but the pocket and the cells bodies keep
+
+    let left = space_analysis.get(Left);
+
+    assert_eq!(left.area, 121 - 1 - 2 - 7);
+    assert!(left.reaches_tail);
+    assert!(!left.is_trapped());
+
+    // Into our own neck
+
+    assert_eq!(space_analysis.get(Down).area, 0);
+}
+
+#[test]
+fn small_spaces_reaching_the_tail_are_not_traps() {
+    let board = board(
+        vec![snake("a", &[(0, 1), (1, 1), (2, 1), (2, 0), (1, 0)])],
+        vec![],
+    );
+    let space_analysis = SpaceAnalysis::new(&game(), &board, &board.snakes[0]);
+
+    let down = space_analysis.get(Down);
+
+    assert_eq!(down.area, 2);
+    assert!(down.reaches_tail);
+    assert!(!down.is_trapped());
+}
+
+#[test]
+fn space_agent_passes_up_food_in_a_trap() {
+    let board = pocket_board(vec![coord(5, 6)]);
+
+    let chosen_move = SpaceAgent.get_move(&game(), &0, &board, &board.snakes[0]);
+
+    assert_eq!(chosen_move, Some(Left));
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use ml_battle_snake::{geometry::Geometry, ml_snake::territory::Territory, Board, Coord};
+
+use common::{board, game, snake};
+
+/// Two snakes facing each other across the middle column, `b` with `extra` more body parts
+fn facing_board(extra: usize) -> Board {
+    let mut b_body = vec![(9, 5), (9, 4), (9, 3)];
+    b_body.extend((0..extra).map(|i| (9, 2 - i as i32)));
+
+    board(
+        vec![snake("a", &[(1, 5), (1, 4), (1, 3)]), snake("b", &b_body)],
+        Vec::new(),
+    )
+}
+
+#[test]
+fn cells_go_to_the_closest_snake() {
+    let territory = Territory::new(&game(), &facing_board(0));
+
+    assert_eq!(territory.owner(Coord { x: 2, y: 5 }), Some("a"));
+    assert_eq!(territory.owner(Coord { x: 0, y: 10 }), Some("a"));
+    assert_eq!(territory.owner(Coord { x: 8, y: 5 }), Some("b"));
+    assert_eq!(territory.owner(Coord { x: 10, y: 0 }), Some("b"));
+
+    // Equally far from equally long snakes, so nobody's
+
+    assert_eq!(territory.owner(Coord { x: 5, y: 5 }), None);
+    assert_eq!(territory.area("a"), territory.area("b"));
+}
+
+#[test]
+fn ties_go_to_the_longer_snake() {
+    let territory = Territory::new(&game(), &facing_board(1));
+
+    assert_eq!(territory.owner(Coord { x: 5, y: 5 }), Some("b"));
+    assert!(territory.area("b") > territory.area("a"));
+    assert!(territory.share("a") + territory.share("b") <= 1.);
+}
+
+#[test]
+fn plane_marks_cells_by_owner() {
+    let geometry = Geometry::new(11, 11);
+    let territory = Territory::new(&game(), &facing_board(0));
+    let plane = territory.plane("a");
+
+    assert_eq!(plane.len(), geometry.area());
+    assert_eq!(plane[geometry.pack(Coord { x: 2, y: 5 })], 1.);
+    assert_eq!(plane[geometry.pack(Coord { x: 8, y: 5 })], -1.);
+    assert_eq!(plane[geometry.pack(Coord { x: 5, y: 5 })], 0.);
+
+    // Body parts other than tails are nobody's
+
+    assert_eq!(plane[geometry.pack(Coord { x: 1, y: 4 })], 0.);
+    assert_eq!(territory.owner(Coord { x: 1, y: 4 }), None);
+}